@@ -12,7 +12,12 @@ impl std::str::FromStr for sepal_length_cm {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let f = s.parse::<f64>().unwrap();
+        if s.is_empty() {
+            return Ok(sepal_length_cm::Null);
+        }
+        let f = s
+            .parse::<f64>()
+            .map_err(|e| format!("Invalid sepal_length_cm value '{s}': {e}"))?;
         Ok(sepal_length_cm::Float(f))
     }
 }
@@ -27,7 +32,12 @@ impl std::str::FromStr for sepal_width_cm {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let f = s.parse::<f64>().unwrap();
+        if s.is_empty() {
+            return Ok(sepal_width_cm::Null);
+        }
+        let f = s
+            .parse::<f64>()
+            .map_err(|e| format!("Invalid sepal_width_cm value '{s}': {e}"))?;
         Ok(sepal_width_cm::Float(f))
     }
 }
@@ -42,7 +52,12 @@ impl std::str::FromStr for petal_length_cm {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let f = s.parse::<f64>().unwrap();
+        if s.is_empty() {
+            return Ok(petal_length_cm::Null);
+        }
+        let f = s
+            .parse::<f64>()
+            .map_err(|e| format!("Invalid petal_length_cm value '{s}': {e}"))?;
         Ok(petal_length_cm::Float(f))
     }
 }
@@ -57,141 +72,411 @@ impl std::str::FromStr for petal_width_cm {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let f = s.parse::<f64>().unwrap();
+        if s.is_empty() {
+            return Ok(petal_width_cm::Null);
+        }
+        let f = s
+            .parse::<f64>()
+            .map_err(|e| format!("Invalid petal_width_cm value '{s}': {e}"))?;
         Ok(petal_width_cm::Float(f))
     }
 }
 
-create_enum!(target;
+create_enum!(pub target;
 "Iris-setosa" => Iris_setosa,
 "Iris-versicolor" => Iris_versicolor,
 "Iris-virginica" => Iris_virginica,
 Null,
 );
 
-#[derive(Debug)]
-pub enum CsvColumn {
-    sepal_length_cm(Vec<sepal_length_cm>),
-    sepal_width_cm(Vec<sepal_width_cm>),
-    petal_length_cm(Vec<petal_length_cm>),
-    petal_width_cm(Vec<petal_width_cm>),
-    target(Vec<target>),
+#[derive(Debug, Clone, Copy)]
+pub enum IrisColumn<'a> {
+    sepal_length_cm(&'a [sepal_length_cm]),
+    sepal_width_cm(&'a [sepal_width_cm]),
+    petal_length_cm(&'a [petal_length_cm]),
+    petal_width_cm(&'a [petal_width_cm]),
+    target(&'a [target]),
 }
 
-pub struct CsvDataFrame {
-    pub sepal_length_cm: CsvColumn,
-    pub sepal_width_cm: CsvColumn,
-    pub petal_length_cm: CsvColumn,
-    pub petal_width_cm: CsvColumn,
-    pub target: CsvColumn,
+#[derive(Debug, Clone, PartialEq)]
+pub enum IrisFrameError {
+    MissingColumn {
+        column: &'static str,
+    },
+    InvalidValue {
+        column: &'static str,
+        /// `None` when reading a single row with serde
+        row: Option<usize>,
+        raw: String,
+        expected: &'static str,
+    },
 }
-impl CsvDataFrame {
+
+impl std::fmt::Display for IrisFrameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingColumn { column } => write!(f, "missing column `{column}`"),
+            Self::InvalidValue {
+                column,
+                row: Some(row),
+                raw,
+                expected,
+            } => {
+                write!(f, "column `{column}`, row {row}: `{raw}` is not {expected}")
+            }
+            Self::InvalidValue {
+                column,
+                row: None,
+                raw,
+                expected,
+            } => {
+                write!(f, "column `{column}`: `{raw}` is not {expected}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for IrisFrameError {}
+
+pub struct IrisFrame {
+    pub sepal_length_cm: Vec<sepal_length_cm>,
+    pub sepal_width_cm: Vec<sepal_width_cm>,
+    pub petal_length_cm: Vec<petal_length_cm>,
+    pub petal_width_cm: Vec<petal_width_cm>,
+    pub target: Vec<target>,
+}
+impl IrisFrame {
+    /// Panics if `dataset` doesn't match the generated types,
+    /// use `IrisFrame::try_from` to handle the error
     pub fn new(dataset: &CsvDataset) -> Self {
-        let (index, _) = dataset
+        Self::try_from(dataset).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    fn sepal_length_cm_from(val: &CsvAny) -> Option<sepal_length_cm> {
+        match val {
+            CsvAny::Float(f) => Some(sepal_length_cm::Float(*f)),
+            CsvAny::Int(i) => Some(sepal_length_cm::Float(*i as f64)),
+            CsvAny::BigInt(i) => Some(sepal_length_cm::Float(*i as f64)),
+            CsvAny::Null => Some(sepal_length_cm::Null),
+            CsvAny::Empty => Some(sepal_length_cm::Null),
+
+            _ => None,
+        }
+    }
+
+    fn sepal_width_cm_from(val: &CsvAny) -> Option<sepal_width_cm> {
+        match val {
+            CsvAny::Float(f) => Some(sepal_width_cm::Float(*f)),
+            CsvAny::Int(i) => Some(sepal_width_cm::Float(*i as f64)),
+            CsvAny::BigInt(i) => Some(sepal_width_cm::Float(*i as f64)),
+            CsvAny::Null => Some(sepal_width_cm::Null),
+            CsvAny::Empty => Some(sepal_width_cm::Null),
+
+            _ => None,
+        }
+    }
+
+    fn petal_length_cm_from(val: &CsvAny) -> Option<petal_length_cm> {
+        match val {
+            CsvAny::Float(f) => Some(petal_length_cm::Float(*f)),
+            CsvAny::Int(i) => Some(petal_length_cm::Float(*i as f64)),
+            CsvAny::BigInt(i) => Some(petal_length_cm::Float(*i as f64)),
+            CsvAny::Null => Some(petal_length_cm::Null),
+            CsvAny::Empty => Some(petal_length_cm::Null),
+
+            _ => None,
+        }
+    }
+
+    fn petal_width_cm_from(val: &CsvAny) -> Option<petal_width_cm> {
+        match val {
+            CsvAny::Float(f) => Some(petal_width_cm::Float(*f)),
+            CsvAny::Int(i) => Some(petal_width_cm::Float(*i as f64)),
+            CsvAny::BigInt(i) => Some(petal_width_cm::Float(*i as f64)),
+            CsvAny::Null => Some(petal_width_cm::Null),
+            CsvAny::Empty => Some(petal_width_cm::Null),
+
+            _ => None,
+        }
+    }
+
+    fn target_from(val: &CsvAny) -> Option<target> {
+        match val {
+            CsvAny::Str(s) => target::from_str(s).ok(),
+            CsvAny::Null => Some(target::Null),
+            CsvAny::Empty => Some(target::Null),
+
+            _ => None,
+        }
+    }
+
+    pub fn get_columns(&self) -> [IrisColumn<'_>; 5] {
+        [
+            IrisColumn::sepal_length_cm(&self.sepal_length_cm),
+            IrisColumn::sepal_width_cm(&self.sepal_width_cm),
+            IrisColumn::petal_length_cm(&self.petal_length_cm),
+            IrisColumn::petal_width_cm(&self.petal_width_cm),
+            IrisColumn::target(&self.target),
+        ]
+    }
+    /// How many times each value of `target` appears
+    pub fn target_value_counts(&self) -> std::collections::HashMap<target, usize> {
+        let mut counts = std::collections::HashMap::new();
+        for value in &self.target {
+            *counts.entry(*value).or_default() += 1;
+        }
+        counts
+    }
+}
+impl TryFrom<&CsvDataset<'_>> for IrisFrame {
+    type Error = IrisFrameError;
+
+    fn try_from(dataset: &CsvDataset<'_>) -> Result<Self, Self::Error> {
+        let index = dataset
             .names
+            .iter()
+            .position(|cl| cl.sanitized.0 == "sepal_length_cm")
+            .ok_or(IrisFrameError::MissingColumn {
+                column: "sepal length (cm)",
+            })?;
+        let sepal_length_cm = dataset.values[index]
             .iter()
             .enumerate()
-            .find(|(index, cl)| &cl.sanitized.0 == "sepal_length_cm")
-            .unwrap();
-        let sepal_length_cm = CsvColumn::sepal_length_cm(
-            dataset.values[index]
-                .iter()
-                .map(|val| match val {
-                    CsvAny::Float(f) => sepal_length_cm::Float(*f),
-                    CsvAny::Null => sepal_length_cm::Null,
-
-                    _ => panic!(),
+            .map(|(row, val)| {
+                Self::sepal_length_cm_from(val).ok_or_else(|| IrisFrameError::InvalidValue {
+                    column: "sepal length (cm)",
+                    row: Some(row),
+                    raw: val.to_string(),
+                    expected: "a valid f64",
                 })
-                .collect::<Vec<sepal_length_cm>>(),
-        );
+            })
+            .collect::<Result<Vec<sepal_length_cm>, _>>()?;
 
-        let (index, _) = dataset
+        let index = dataset
             .names
+            .iter()
+            .position(|cl| cl.sanitized.0 == "sepal_width_cm")
+            .ok_or(IrisFrameError::MissingColumn {
+                column: "sepal width (cm)",
+            })?;
+        let sepal_width_cm = dataset.values[index]
             .iter()
             .enumerate()
-            .find(|(index, cl)| &cl.sanitized.0 == "sepal_width_cm")
-            .unwrap();
-        let sepal_width_cm = CsvColumn::sepal_width_cm(
-            dataset.values[index]
-                .iter()
-                .map(|val| match val {
-                    CsvAny::Float(f) => sepal_width_cm::Float(*f),
-                    CsvAny::Null => sepal_width_cm::Null,
-
-                    _ => panic!(),
+            .map(|(row, val)| {
+                Self::sepal_width_cm_from(val).ok_or_else(|| IrisFrameError::InvalidValue {
+                    column: "sepal width (cm)",
+                    row: Some(row),
+                    raw: val.to_string(),
+                    expected: "a valid f64",
                 })
-                .collect::<Vec<sepal_width_cm>>(),
-        );
+            })
+            .collect::<Result<Vec<sepal_width_cm>, _>>()?;
 
-        let (index, _) = dataset
+        let index = dataset
             .names
+            .iter()
+            .position(|cl| cl.sanitized.0 == "petal_length_cm")
+            .ok_or(IrisFrameError::MissingColumn {
+                column: "petal length (cm)",
+            })?;
+        let petal_length_cm = dataset.values[index]
             .iter()
             .enumerate()
-            .find(|(index, cl)| &cl.sanitized.0 == "petal_length_cm")
-            .unwrap();
-        let petal_length_cm = CsvColumn::petal_length_cm(
-            dataset.values[index]
-                .iter()
-                .map(|val| match val {
-                    CsvAny::Float(f) => petal_length_cm::Float(*f),
-                    CsvAny::Null => petal_length_cm::Null,
-
-                    _ => panic!(),
+            .map(|(row, val)| {
+                Self::petal_length_cm_from(val).ok_or_else(|| IrisFrameError::InvalidValue {
+                    column: "petal length (cm)",
+                    row: Some(row),
+                    raw: val.to_string(),
+                    expected: "a valid f64",
                 })
-                .collect::<Vec<petal_length_cm>>(),
-        );
+            })
+            .collect::<Result<Vec<petal_length_cm>, _>>()?;
 
-        let (index, _) = dataset
+        let index = dataset
             .names
+            .iter()
+            .position(|cl| cl.sanitized.0 == "petal_width_cm")
+            .ok_or(IrisFrameError::MissingColumn {
+                column: "petal width (cm)",
+            })?;
+        let petal_width_cm = dataset.values[index]
             .iter()
             .enumerate()
-            .find(|(index, cl)| &cl.sanitized.0 == "petal_width_cm")
-            .unwrap();
-        let petal_width_cm = CsvColumn::petal_width_cm(
-            dataset.values[index]
-                .iter()
-                .map(|val| match val {
-                    CsvAny::Float(f) => petal_width_cm::Float(*f),
-                    CsvAny::Null => petal_width_cm::Null,
-
-                    _ => panic!(),
+            .map(|(row, val)| {
+                Self::petal_width_cm_from(val).ok_or_else(|| IrisFrameError::InvalidValue {
+                    column: "petal width (cm)",
+                    row: Some(row),
+                    raw: val.to_string(),
+                    expected: "a valid f64",
                 })
-                .collect::<Vec<petal_width_cm>>(),
-        );
+            })
+            .collect::<Result<Vec<petal_width_cm>, _>>()?;
 
-        let (index, _) = dataset
+        let index = dataset
             .names
+            .iter()
+            .position(|cl| cl.sanitized.0 == "target")
+            .ok_or(IrisFrameError::MissingColumn { column: "target" })?;
+        let target = dataset.values[index]
             .iter()
             .enumerate()
-            .find(|(index, cl)| &cl.sanitized.0 == "target")
-            .unwrap();
-        let target = CsvColumn::target(
-            dataset.values[index]
-                .iter()
-                .map(|val| match val {
-                    CsvAny::Str(s) => target::from_str(s).unwrap(),
-                    CsvAny::Null => target::Null,
-
-                    _ => panic!(),
+            .map(|(row, val)| {
+                Self::target_from(val).ok_or_else(|| IrisFrameError::InvalidValue {
+                    column: "target",
+                    row: Some(row),
+                    raw: val.to_string(),
+                    expected: "a `target` variant",
                 })
-                .collect::<Vec<target>>(),
-        );
+            })
+            .collect::<Result<Vec<target>, _>>()?;
 
-        CsvDataFrame {
+        Ok(IrisFrame {
             sepal_length_cm,
             sepal_width_cm,
             petal_length_cm,
             petal_width_cm,
             target,
-        }
+        })
     }
-    pub fn get_columns(&self) -> [&CsvColumn; 5] {
-        [
-            &self.sepal_length_cm,
-            &self.sepal_width_cm,
-            &self.petal_length_cm,
-            &self.petal_width_cm,
-            &self.target,
-        ]
+}
+
+#[derive(Debug, Clone, PartialEq, csv_deserializer::serde::Deserialize)]
+#[serde(crate = "csv_deserializer::serde", try_from = "RawIrisRow")]
+pub struct IrisRow {
+    pub sepal_length_cm: sepal_length_cm,
+    pub sepal_width_cm: sepal_width_cm,
+    pub petal_length_cm: petal_length_cm,
+    pub petal_width_cm: petal_width_cm,
+    pub target: target,
+}
+
+#[derive(csv_deserializer::serde::Deserialize)]
+#[serde(crate = "csv_deserializer::serde")]
+struct RawIrisRow {
+    #[serde(rename = "sepal length (cm)")]
+    sepal_length_cm: String,
+    #[serde(rename = "sepal width (cm)")]
+    sepal_width_cm: String,
+    #[serde(rename = "petal length (cm)")]
+    petal_length_cm: String,
+    #[serde(rename = "petal width (cm)")]
+    petal_width_cm: String,
+    #[serde(rename = "target")]
+    target: String,
+}
+
+impl TryFrom<RawIrisRow> for IrisRow {
+    type Error = IrisFrameError;
+
+    fn try_from(raw: RawIrisRow) -> Result<Self, Self::Error> {
+        let null_values = csv_deserializer::NullValues(vec!["NA"]);
+        let number_format = csv_deserializer::number_format::NumberFormat {
+            decimal_separator: '.',
+            grouping_separator: None,
+            percent: false,
+            currency_symbols: vec![],
+        };
+        Ok(IrisRow {
+            sepal_length_cm: IrisFrame::sepal_length_cm_from(&CsvAny::parse_as(
+                &raw.sepal_length_cm,
+                csv_deserializer::dataset_info::ColumnKind::Float,
+                &null_values,
+                &number_format,
+            ))
+            .ok_or_else(|| IrisFrameError::InvalidValue {
+                column: "sepal length (cm)",
+                row: None,
+                raw: raw.sepal_length_cm.clone(),
+                expected: "a valid f64",
+            })?,
+            sepal_width_cm: IrisFrame::sepal_width_cm_from(&CsvAny::parse_as(
+                &raw.sepal_width_cm,
+                csv_deserializer::dataset_info::ColumnKind::Float,
+                &null_values,
+                &number_format,
+            ))
+            .ok_or_else(|| IrisFrameError::InvalidValue {
+                column: "sepal width (cm)",
+                row: None,
+                raw: raw.sepal_width_cm.clone(),
+                expected: "a valid f64",
+            })?,
+            petal_length_cm: IrisFrame::petal_length_cm_from(&CsvAny::parse_as(
+                &raw.petal_length_cm,
+                csv_deserializer::dataset_info::ColumnKind::Float,
+                &null_values,
+                &number_format,
+            ))
+            .ok_or_else(|| IrisFrameError::InvalidValue {
+                column: "petal length (cm)",
+                row: None,
+                raw: raw.petal_length_cm.clone(),
+                expected: "a valid f64",
+            })?,
+            petal_width_cm: IrisFrame::petal_width_cm_from(&CsvAny::parse_as(
+                &raw.petal_width_cm,
+                csv_deserializer::dataset_info::ColumnKind::Float,
+                &null_values,
+                &number_format,
+            ))
+            .ok_or_else(|| IrisFrameError::InvalidValue {
+                column: "petal width (cm)",
+                row: None,
+                raw: raw.petal_width_cm.clone(),
+                expected: "a valid f64",
+            })?,
+            target: IrisFrame::target_from(&CsvAny::parse_as(
+                &raw.target,
+                csv_deserializer::dataset_info::ColumnKind::Categorical,
+                &null_values,
+                &number_format,
+            ))
+            .ok_or_else(|| IrisFrameError::InvalidValue {
+                column: "target",
+                row: None,
+                raw: raw.target.clone(),
+                expected: "a `target` variant",
+            })?,
+        })
+    }
+}
+
+impl IrisFrame {
+    pub fn len(&self) -> usize {
+        self.sepal_length_cm.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = IrisRow> + '_ {
+        (0..self.len()).map(|index| IrisRow {
+            sepal_length_cm: self.sepal_length_cm[index],
+            sepal_width_cm: self.sepal_width_cm[index],
+            petal_length_cm: self.petal_length_cm[index],
+            petal_width_cm: self.petal_width_cm[index],
+            target: self.target[index],
+        })
+    }
+}
+
+impl FromIterator<IrisRow> for IrisFrame {
+    fn from_iter<I: IntoIterator<Item = IrisRow>>(iter: I) -> Self {
+        let mut df = IrisFrame {
+            sepal_length_cm: Vec::new(),
+            sepal_width_cm: Vec::new(),
+            petal_length_cm: Vec::new(),
+            petal_width_cm: Vec::new(),
+            target: Vec::new(),
+        };
+        for row in iter {
+            df.sepal_length_cm.push(row.sepal_length_cm);
+            df.sepal_width_cm.push(row.sepal_width_cm);
+            df.petal_length_cm.push(row.petal_length_cm);
+            df.petal_width_cm.push(row.petal_width_cm);
+            df.target.push(row.target);
+        }
+        df
     }
 }
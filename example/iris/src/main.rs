@@ -21,32 +21,41 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     // The iris.rs file is generate with the binary of csv_deserializer
 
-    // Then inside the iris.rs file a CsvDataFrame is used
+    // Then inside the iris.rs file a IrisFrame is used
     // as the main struct which contains all the data
-    let df = CsvDataFrame::new(&dataset);
+    let df = IrisFrame::try_from(&dataset)?;
 
     // Do ETL stuffes in a type safe way but it comes at less
     // flexibility sometimes, so you can always use CsvDataset which
     // use CsvAny as the type for every cell
 
-    // Can destruct the column wrapper called CsvColumn with if let
-    if let CsvColumn::target(target) = &df.target
-        && let CsvColumn::petal_length_cm(_pet_length) = &df.petal_length_cm
-    {
-        target.iter().for_each(|x| match x {
-            target::Iris_setosa => todo!(),
-            target::Iris_versicolor => todo!(),
-            target::Iris_virginica => todo!(),
-            target::Null => todo!(),
-        });
+    // Every field is a Vec of its own generated enum,
+    // no need to destruct a wrapper
+    df.target.iter().for_each(|x| match x {
+        target::Iris_setosa => todo!(),
+        target::Iris_versicolor => todo!(),
+        target::Iris_virginica => todo!(),
+        target::Null => todo!(),
+    });
+
+    // Or work one record at a time, IrisRow has a typed field for each column
+    let big_petals = df
+        .rows()
+        .filter(|row| matches!(row.petal_length_cm, petal_length_cm::Float(l) if l > 5.0))
+        .collect::<IrisFrame>();
+
+    // IrisRow can also be read straight from a csv::Reader
+    let mut rdr = csv::Reader::from_path(path)?;
+    for row in rdr.deserialize::<IrisRow>() {
+        let row = row?;
     }
 
-    // Can use a list of all columns
-    // make sure to use auto-completion
+    // Can use a list of all columns wrapped in the
+    // IrisColumn view, make sure to use auto-completion
     // for match arms
     for col in df.get_columns() {
         match col {
-            CsvColumn::sepal_length_cm(sepal_length_cms) => {
+            IrisColumn::sepal_length_cm(sepal_length_cms) => {
                 sepal_length_cms.iter().for_each(|x|{
                     match x {
                         sepal_length_cm::Float(f) => todo!(),
@@ -54,10 +63,10 @@ fn main() -> Result<(), Box<dyn Error>> {
                     }
                 })
             },
-            CsvColumn::sepal_width_cm(sepal_width_cms) => todo!(),
-            CsvColumn::petal_length_cm(petal_length_cms) => todo!(),
-            CsvColumn::petal_width_cm(petal_width_cms) => todo!(),
-            CsvColumn::target(targets) => {
+            IrisColumn::sepal_width_cm(sepal_width_cms) => todo!(),
+            IrisColumn::petal_length_cm(petal_length_cms) => todo!(),
+            IrisColumn::petal_width_cm(petal_width_cms) => todo!(),
+            IrisColumn::target(targets) => {
                 targets.iter().for_each(|x|match x{
                     target::Iris_setosa => todo!(),
                     target::Iris_versicolor => todo!(),
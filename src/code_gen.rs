@@ -0,0 +1,76 @@
+use std::path::Path;
+
+use crate::{
+    enum_gen::{gen_shared_enums, generate_enums_from, shared_columns},
+    gen_options::{GenOptions, TypeNames},
+    sanitizer::sanitize_identifier,
+    schema::{Schema, SchemaFileError},
+    struct_gen::{gen_row_struct, gen_struct},
+};
+
+/// The attributes to put at the top of the file holding the generated code
+pub const GENERATED_FILE_HEADER: &str = "#![allow(unused,non_snake_case,non_camel_case_types)]";
+
+/// All the code of a csv: the imports, the enums, `CsvDataFrame` and `CsvRow`,
+/// wrapped in `GenOptions::module` if any
+pub fn gen_rust_code(schema: &Schema, options: &GenOptions) -> String {
+    let enums = generate_enums_from(schema, options);
+    let struc = gen_struct(schema, options);
+    let row = gen_row_struct(schema, options);
+    let import = gen_imports();
+    options.wrap_in_module(format!("{import}\n{enums}\n{struc}\n{row}"))
+}
+
+/// Like `gen_rust_code` but from a schema saved with `Schema::save`,
+/// the csv is not needed
+///
+/// # Examples
+/// ```no_run
+/// use std::path::Path;
+/// use csv_deserializer::{code_gen::gen_rust_code_from_file, gen_options::GenOptions};
+///
+/// let code = gen_rust_code_from_file(Path::new("iris.toml"), &GenOptions::default()).unwrap();
+/// std::fs::write("src/iris.rs", code).unwrap();
+/// ```
+pub fn gen_rust_code_from_file(path: &Path, options: &GenOptions) -> Result<String, SchemaFileError> {
+    Schema::from_file(path).map(|schema| gen_rust_code(&schema, options))
+}
+
+/// The code of several files, each one in a module named after it.
+/// The enums of the columns in common are generated once outside of them
+pub fn gen_files_rust_code(schemas: &[(String, Schema)], options: &GenOptions) -> String {
+    let shared = shared_columns(&schemas.iter().map(|(_, schema)| schema).collect::<Vec<_>>());
+    let shared_enums = gen_shared_enums(&shared, options);
+    let files_options = GenOptions {
+        shared_enums: shared.iter().map(|column| column.ident.clone()).collect(),
+        module: None,
+        ..options.clone()
+    };
+    let files = schemas
+        .iter()
+        .map(|(file_stem, schema)| {
+            let file_options = GenOptions {
+                names: TypeNames::with_prefix(file_stem),
+                module: Some(module_name(file_stem)),
+                ..files_options.clone()
+            };
+            gen_rust_code(schema, &file_options)
+        })
+        .collect::<String>();
+    let import = gen_imports();
+    options.wrap_in_module(format!("{import}\n{shared_enums}\n{files}"))
+}
+
+/// The module holding the code of a file, `Orders.csv` gives `orders`
+pub fn module_name(file_stem: &str) -> String {
+    sanitize_identifier(file_stem).to_lowercase()
+}
+
+fn gen_imports() -> String {
+    String::from(
+        "\
+use csv_deserializer::{create_enum, csv_dataset::CsvDataset, csv_types::CsvAny};
+use std::str::FromStr;
+\n",
+    )
+}
@@ -1,4 +1,4 @@
-use std::io;
+use std::{fmt::Write, io};
 
 use csv::Reader;
 
@@ -6,19 +6,82 @@ use crate::{
     ColName, NullValues, RawCsvValue, RemovedColumn, SanitizedStr, ValueNamesMut, ValueNamesView,
     csv_types::CsvAny,
     dataset_info::{ColumnInfo, Variant},
+    number_format::{NumberFormat, NumericMarker},
+    overrides::{ColumnType, OverrideViolation, TypeOverrides},
     sanitizer::sanitize_identifier,
 };
 
+/// Options used to convert every raw cell in a `CsvAny`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParseOptions {
+    pub number_format: NumberFormat,
+    /// Keep the columns containing codes like `00123` (zip codes, SKUs, ..)
+    /// as `CsvAny::Str` instead of parsing them as integers
+    pub preserve_leading_zeros: bool,
+    /// Raw names of the columns whose values are always `CsvAny::Str`,
+    /// a shorthand for a `ColumnType::Text` override
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub text_columns: Vec<String>,
+    /// Column types forced by the user, they win over inference
+    pub overrides: TypeOverrides,
+    /// Stop keeping the unique values of a column after this many, the distinct
+    /// count becomes approximate and string columns become `ColumnKind::Text`.
+    /// `None` keeps every unique value
+    pub distinct_limit: Option<usize>,
+    /// Make the string columns matching a `Pattern` in every cell `ColumnKind::Semantic`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub semantic_types: bool,
+    /// Look for pairs of columns unique together, see `dataset_info::composite_keys`.
+    /// Every pair of columns is checked, it is slow on wide csvs and finds
+    /// coincidental pairs in small ones
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub composite_keys: bool,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            number_format: NumberFormat::default(),
+            preserve_leading_zeros: true,
+            text_columns: Vec::new(),
+            overrides: TypeOverrides::default(),
+            distinct_limit: None,
+            semantic_types: false,
+            composite_keys: false,
+        }
+    }
+}
+
+impl ParseOptions {
+    /// The type forced on a column by `overrides` or `text_columns`
+    pub fn column_type(&self, raw_column_name: &str) -> Option<ColumnType> {
+        self.overrides.get(raw_column_name).or_else(|| {
+            self.text_columns
+                .iter()
+                .any(|name| name == raw_column_name)
+                .then_some(ColumnType::Text)
+        })
+    }
+}
+
 /// This is a form to represent the dataset
 /// which does not deep typization but can still
 /// be usefull, also info field holds some info about the
 /// types for each column
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CsvDataset<'a> {
     pub names: Vec<ColName>,
     pub values: Vec<Vec<CsvAny>>,
+    #[cfg_attr(feature = "serde", serde(borrow))]
     pub null_values: NullValues<'a>,
     pub info: Vec<ColumnInfo>,
+    pub options: ParseOptions,
+    /// Distinct markers stripped from the numeric values of each column
+    pub markers: Vec<Vec<NumericMarker>>,
+    /// Cells which didn't match the type forced by `ParseOptions::overrides`
+    pub violations: Vec<OverrideViolation>,
 }
 
 impl<'a> CsvDataset<'a> {
@@ -42,7 +105,15 @@ impl<'a> CsvDataset<'a> {
         }
     }
 
-    pub fn new<R: io::Read>(mut reader: Reader<R>, null_values: NullValues<'a>) -> Self {
+    pub fn new<R: io::Read>(reader: Reader<R>, null_values: NullValues<'a>) -> Self {
+        Self::with_options(reader, null_values, ParseOptions::default())
+    }
+
+    pub fn with_options<R: io::Read>(
+        mut reader: Reader<R>,
+        null_values: NullValues<'a>,
+        options: ParseOptions,
+    ) -> Self {
         let names: Vec<ColName> = reader
             .headers()
             .unwrap()
@@ -54,21 +125,104 @@ impl<'a> CsvDataset<'a> {
             })
             .collect();
         let mut values: Vec<Vec<CsvAny>> = (0..names.len()).map(|_| Vec::new()).collect();
-        reader.into_records().for_each(|x| {
+        let mut markers: Vec<Vec<NumericMarker>> = (0..names.len()).map(|_| Vec::new()).collect();
+        let column_types: Vec<Option<ColumnType>> = names
+            .iter()
+            .map(|name| options.column_type(&name.raw))
+            .collect();
+        let mut violations = options
+            .overrides
+            .columns
+            .iter()
+            .map(|(column, expected)| (column, *expected))
+            .chain(options.text_columns.iter().map(|column| (column, ColumnType::Text)))
+            .filter(|(column, _)| !names.iter().any(|name| &name.raw == *column))
+            .map(|(column, expected)| OverrideViolation {
+                column: column.clone(),
+                row: None,
+                raw: String::new(),
+                expected,
+            })
+            .collect::<Vec<_>>();
+        // A column with codes like `00123` is kept as text, every cell
+        // keeps its raw spelling, also the ones looking like numbers.
+        // The numbers read before the first code become text at the end, until
+        // then only the raw cells whose number is written differently (`+7`, `1,234`) are kept
+        let mut has_leading_zeros = vec![false; names.len()];
+        let mut respelled: Vec<Vec<(usize, String)>> = (0..names.len()).map(|_| Vec::new()).collect();
+        let mut spelling = String::new();
+        reader.into_records().enumerate().for_each(|(row, x)| {
             x.unwrap()
                 .iter()
                 .enumerate()
                 .for_each(|(column_index, value)| {
+                    let raw = RawCsvValue(value);
+                    let (csvany, marker) = match column_types[column_index] {
+                        Some(expected) => raw
+                            .as_type(&null_values, &options.number_format, expected)
+                            .unwrap_or_else(|| {
+                                violations.push(OverrideViolation {
+                                    column: names[column_index].raw.clone(),
+                                    row: Some(row),
+                                    raw: value.to_string(),
+                                    expected,
+                                });
+                                (CsvAny::Null, None)
+                            }),
+                        None if has_leading_zeros[column_index] => {
+                            (CsvAny::parse_text(value, &null_values), None)
+                        }
+                        None if options.preserve_leading_zeros && raw.is_leading_zero_code() => {
+                            has_leading_zeros[column_index] = true;
+                            (CsvAny::parse_text(value, &null_values), None)
+                        }
+                        None => {
+                            let (csvany, marker) = raw.as_csvany(&null_values, &options.number_format);
+                            if options.preserve_leading_zeros
+                                && matches!(csvany, CsvAny::Int(_) | CsvAny::BigInt(_) | CsvAny::Float(_))
+                            {
+                                spelling.clear();
+                                write!(spelling, "{csvany}").unwrap();
+                                if spelling != value {
+                                    respelled[column_index].push((row, value.to_string()));
+                                }
+                            }
+                            (csvany, marker)
+                        }
+                    };
                     let k = values.get_mut(column_index).unwrap();
-                    k.push(RawCsvValue(value).as_csvany(&null_values));
+                    k.push(csvany);
+                    let column_markers = &mut markers[column_index];
+                    if let Some(marker) = marker
+                        && !column_markers.contains(&marker)
+                    {
+                        column_markers.push(marker);
+                    }
                 });
         });
+        for (column_index, respelled) in respelled.into_iter().enumerate() {
+            if !has_leading_zeros[column_index] {
+                continue;
+            }
+            let column = &mut values[column_index];
+            column
+                .iter_mut()
+                .filter(|cell| matches!(cell, CsvAny::Int(_) | CsvAny::BigInt(_) | CsvAny::Float(_)))
+                .for_each(|cell| *cell = CsvAny::Str(cell.to_string()));
+            respelled
+                .into_iter()
+                .for_each(|(row, raw)| column[row] = CsvAny::Str(raw));
+            markers[column_index].clear();
+        }
 
         Self {
             names,
             values,
             null_values,
             info: Vec::new(),
+            options,
+            markers,
+            violations,
         }
     }
     pub fn names_and_values_view(&self) -> ValueNamesView<'_> {
@@ -93,28 +247,62 @@ impl<'a> CsvDataset<'a> {
         )
     }
 
-    /// Analyze every cell in the csv file to extract every unique value
-    pub fn populate_column_infos(dataset: &mut Self) {
-        let (value_names_view, info) = dataset.split_view_and_info();
-        let col_name = value_names_view.names;
-
-        for col_name in col_name {
-            let mut col_info = ColumnInfo::new(value_names_view, &col_name.raw);
+    /// Build the `ColumnInfo` of the column at `column_index`,
+    /// a `Null` variant is always part of the unique values
+    pub fn column_info(&self, column_index: usize) -> ColumnInfo {
+        let mut col_info = ColumnInfo::with_distinct_limit(
+            self.names_and_values_view(),
+            &self.names[column_index].raw,
+            self.options.distinct_limit,
+            self.options.semantic_types,
+        );
 
-            if !col_info
-                .unique_values
+        if !col_info
+            .unique_values
+            .iter()
+            .any(|x| x.csvany == CsvAny::Null)
+        {
+            let str = String::from("Null");
+            col_info.unique_values.push(Variant {
+                raw: str.clone(),
+                sanitized: str,
+                csvany: CsvAny::Null,
+                count: 0,
+                first_row: None,
+            });
+        }
+        col_info.markers = self.markers.get(column_index).cloned().unwrap_or_default();
+        col_info.type_override = self.options.column_type(&self.names[column_index].raw);
+        if self.options.semantic_types {
+            col_info.semantic_type = col_info
+                .patterns
                 .iter()
-                .any(|x| x.csvany == CsvAny::Null)
-            {
-                let str = String::from("Null");
-                col_info.unique_values.push(Variant {
-                    raw: str.clone(),
-                    sanitized: str,
-                    csvany: CsvAny::Null,
-                });
-            }
-
-            info.push(col_info.clone());
+                .find(|pattern_share| pattern_share.count == col_info.number_of_strings)
+                .map(|pattern_share| pattern_share.pattern);
         }
+
+        col_info
+    }
+
+    /// Every value of the column at `column_index` with how many cells hold it,
+    /// the most frequent first. `Null` and `Empty` are included when present.
+    /// The counts are always exact, `ParseOptions::distinct_limit` is ignored
+    pub fn value_counts(&self, column_index: usize) -> Vec<(CsvAny, usize)> {
+        let mut counts = ColumnInfo::new(self.names_and_values_view(), &self.names[column_index].raw)
+            .unique_values
+            .into_iter()
+            .map(|variant| (variant.csvany, variant.count))
+            .collect::<Vec<_>>();
+        // stable, so equal counts keep the order of the unique values
+        counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+        counts
+    }
+
+    /// Analyze every cell in the csv file to extract every unique value
+    pub fn populate_column_infos(dataset: &mut Self) {
+        let infos = (0..dataset.names.len())
+            .map(|column_index| dataset.column_info(column_index))
+            .collect::<Vec<ColumnInfo>>();
+        dataset.info.extend(infos);
     }
 }
@@ -1,23 +1,43 @@
+use crate::{NullValues, RawCsvValue, dataset_info::ColumnKind, number_format::NumberFormat};
+
 
 /// Represents any possible value in a CSV cell.
 ///
 /// # Examples
 /// Can use into to convert to the corresponding CsvAny variant
 /// ```
-/// use csv_deserializer::csv_type::CsvAny;
+/// use csv_deserializer::csv_types::CsvAny;
 ///
 /// let val: CsvAny = 42.into();
 /// 
 /// assert_eq!(val, CsvAny::Int(42));
 /// ```
 #[derive(Debug, PartialEq, PartialOrd, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CsvAny {
     Str(String),
     Int(i64),
+    /// Integers which don't fit in `i64`, like `u64` ids above `i64::MAX`
+    BigInt(i128),
     Float(f64),
     Null,  // to represent null values
     Empty, // if it is just empty
 }
+/// Writes the value back as it would appear in the csv file,
+/// `Null` is written as `Null` since the original null string is lost
+impl std::fmt::Display for CsvAny {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvAny::Str(s) => write!(f, "{s}"),
+            CsvAny::Int(i) => write!(f, "{i}"),
+            CsvAny::BigInt(i) => write!(f, "{i}"),
+            CsvAny::Float(x) => write!(f, "{x}"),
+            CsvAny::Null => write!(f, "Null"),
+            CsvAny::Empty => Ok(()),
+        }
+    }
+}
+
 impl From<&str> for CsvAny {
     fn from(val: &str) -> Self {
         CsvAny::Str(val.to_string())
@@ -29,6 +49,65 @@ impl From<i64> for CsvAny {
         CsvAny::Int(val)
     }
 }
+
+impl CsvAny {
+    /// Convert a raw csv cell like `CsvDataset::new` does
+    pub fn parse(raw: &str, null_values: &NullValues, number_format: &NumberFormat) -> Self {
+        RawCsvValue(raw).as_csvany(null_values, number_format).0
+    }
+
+    /// Convert a raw csv cell of a text column, numbers are kept as `Str`
+    pub fn parse_text(raw: &str, null_values: &NullValues) -> Self {
+        RawCsvValue(raw).as_text(null_values)
+    }
+
+    /// Convert a raw csv cell of a column whose kind is known, like the values
+    /// of a `CsvDataset` column with that type: the text kinds keep numbers
+    /// as `Str` and float columns turn whole numbers into `Float`
+    pub fn parse_as(raw: &str, kind: ColumnKind, null_values: &NullValues, number_format: &NumberFormat) -> Self {
+        match kind {
+            ColumnKind::Categorical | ColumnKind::Text | ColumnKind::Semantic(_) => Self::parse_text(raw, null_values),
+            ColumnKind::Float => match Self::parse(raw, null_values, number_format) {
+                CsvAny::Int(i) => CsvAny::Float(i as f64),
+                CsvAny::BigInt(i) => CsvAny::Float(i as f64),
+                other => other,
+            },
+            ColumnKind::Int(_) | ColumnKind::Mixed => Self::parse(raw, null_values, number_format),
+        }
+    }
+
+    /// Like `partial_cmp`, but total: floats are compared with `f64::total_cmp`,
+    /// so `NaN` sorts after every other float instead of having no order
+    pub fn total_cmp(&self, other: &Self) -> std::cmp::Ordering {
+        match (self, other) {
+            (CsvAny::Float(a), CsvAny::Float(b)) => a.total_cmp(b),
+            (CsvAny::Str(a), CsvAny::Str(b)) => a.cmp(b),
+            (CsvAny::Int(a), CsvAny::Int(b)) => a.cmp(b),
+            (CsvAny::BigInt(a), CsvAny::BigInt(b)) => a.cmp(b),
+            _ => self.variant_index().cmp(&other.variant_index()),
+        }
+    }
+
+    /// The position of the variant in the enum, the order of `partial_cmp` across variants
+    fn variant_index(&self) -> u8 {
+        match self {
+            CsvAny::Str(_) => 0,
+            CsvAny::Int(_) => 1,
+            CsvAny::BigInt(_) => 2,
+            CsvAny::Float(_) => 3,
+            CsvAny::Null => 4,
+            CsvAny::Empty => 5,
+        }
+    }
+
+    /// `Int` if `val` fits in `i64`, `BigInt` otherwise
+    pub fn from_integer(val: i128) -> Self {
+        match i64::try_from(val) {
+            Ok(int) => CsvAny::Int(int),
+            Err(_) => CsvAny::BigInt(val),
+        }
+    }
+}
 impl From<f64> for CsvAny{
     fn from(value: f64) -> Self {
         CsvAny::Float(value)
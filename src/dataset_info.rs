@@ -1,10 +1,24 @@
-use std::fmt::Display;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+    str::FromStr,
+};
 
 use itertools::Itertools;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
-use crate::{ColName, CsvAny, ValueNamesView, sanitizer::sanitize_identifier};
+use crate::{
+    ColName, CsvAny, ValueNamesView,
+    number_format::NumericMarker,
+    overrides::ColumnType,
+    patterns::{Pattern, PatternShare},
+    sanitizer::sanitize_identifier,
+    sketch::{Cardinality, HEAVY_HITTERS_CAPACITY, HeavyHitters, HyperLogLog, ValueCount},
+    stats::{NumericStats, StreamingStats},
+};
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColumnInfo {
     pub column_name: ColName,
     pub number_of_empties: u32,
@@ -13,17 +27,164 @@ pub struct ColumnInfo {
     pub number_of_floats: u32,
     pub number_of_ints: u32,
     pub unique_values: Vec<Variant>,
+    /// Units or markers stripped while parsing the numeric values
+    pub markers: Vec<NumericMarker>,
+    /// Smallest integer in the column, `None` if there are no integers
+    pub int_min: Option<i128>,
+    /// Biggest integer in the column, `None` if there are no integers
+    pub int_max: Option<i128>,
+    /// The type forced by the user, see `TypeOverrides`
+    pub type_override: Option<ColumnType>,
+    /// Statistics of the ints and floats, `None` if there are none
+    pub stats: Option<NumericStats>,
+    /// Unique values, `Null` and `Empty` are not counted.
+    /// When `Approximate`, `unique_values` only holds `Null` and `Empty`
+    pub distinct: Cardinality,
+    /// The most frequent values, see `HeavyHitters`
+    pub top_values: Vec<ValueCount>,
+    /// The patterns matched by the strings, the most common first.
+    /// Only detected when `ParseOptions::semantic_types` is on, empty otherwise
+    pub patterns: Vec<PatternShare>,
+    /// The pattern of every string of the column, set by `CsvDataset::column_info`
+    /// when `ParseOptions::semantic_types` is on
+    pub semantic_type: Option<Pattern>,
+}
+
+/// The kind of values held by a column, it drives the code generation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    /// Integers of the given rust type
+    Int(&'static str),
+    Float,
+    /// Strings with a variant for each unique value
+    Categorical,
+    /// Free text, from a `ColumnType::Text` override or a string column
+    /// with more unique values than `ParseOptions::distinct_limit`
+    Text,
+    /// Numbers and strings in the same column
+    Mixed,
+    /// Strings of a known shape, held by the validated type of the pattern
+    Semantic(Pattern),
+}
+
+impl ColumnKind {
+    /// Whether the generated values of the column can be `HashMap` keys,
+    /// floats can't
+    pub fn is_hashable(&self) -> bool {
+        !matches!(self, ColumnKind::Float | ColumnKind::Mixed)
+    }
+}
+
+impl From<ColumnType> for ColumnKind {
+    fn from(column_type: ColumnType) -> Self {
+        match column_type {
+            ColumnType::Int(int_type) => ColumnKind::Int(int_type),
+            ColumnType::Float => ColumnKind::Float,
+            ColumnType::Text => ColumnKind::Text,
+            ColumnType::Categorical => ColumnKind::Categorical,
+        }
+    }
+}
+
+/// The names of `ColumnType` and `Pattern` plus `mixed`
+impl FromStr for ColumnKind {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "mixed" => Ok(ColumnKind::Mixed),
+            other => match other.parse::<Pattern>() {
+                Ok(pattern) => Ok(ColumnKind::Semantic(pattern)),
+                Err(_) => other.parse::<ColumnType>().map(ColumnKind::from),
+            },
+        }
+    }
+}
+
+impl Display for ColumnKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColumnKind::Int(int_type) => write!(f, "{int_type}"),
+            ColumnKind::Float => write!(f, "float"),
+            ColumnKind::Categorical => write!(f, "categorical"),
+            ColumnKind::Text => write!(f, "text"),
+            ColumnKind::Mixed => write!(f, "mixed"),
+            ColumnKind::Semantic(pattern) => write!(f, "{pattern}"),
+        }
+    }
+}
+
+/// Serialized as its name, like `ColumnType`
+impl Serialize for ColumnKind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ColumnKind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Variant {
     pub raw: String,
     pub sanitized: String,
     pub csvany: CsvAny,
+    /// How many cells hold the value
+    pub count: usize,
+    /// Index of the first record holding the value, the header is not counted.
+    /// `None` for the `Null` variant added to columns without nulls
+    pub first_row: Option<usize>,
+}
+
+/// A hashable `CsvAny`, to count the unique values without sorting the column
+#[derive(Hash, PartialEq, Eq)]
+enum ValueKey<'a> {
+    Str(&'a str),
+    Int(i128),
+    Float(u64),
+    Null,
+    Empty,
+}
+
+/// A unique value while it is counted
+struct Seen<'a> {
+    value: &'a CsvAny,
+    count: usize,
+    first_row: usize,
+}
+
+impl<'a> From<&'a CsvAny> for ValueKey<'a> {
+    fn from(value: &'a CsvAny) -> Self {
+        match value {
+            CsvAny::Str(s) => ValueKey::Str(s),
+            CsvAny::Int(i) => ValueKey::Int(i128::from(*i)),
+            CsvAny::BigInt(i) => ValueKey::Int(*i),
+            CsvAny::Float(f) => ValueKey::Float(f.to_bits()),
+            CsvAny::Null => ValueKey::Null,
+            CsvAny::Empty => ValueKey::Empty,
+        }
+    }
 }
 
 impl ColumnInfo {
     pub fn new(names_and_values_view: ValueNamesView, column_name: &str) -> Self {
+        Self::with_distinct_limit(names_and_values_view, column_name, None, false)
+    }
+
+    /// Like `new`, but the unique values are only kept up to `distinct_limit`,
+    /// after that the distinct count is estimated and the memory stays bounded.
+    /// The strings are matched against the `Pattern`s only with `detect_patterns`
+    pub fn with_distinct_limit<'a>(
+        names_and_values_view: ValueNamesView<'a>,
+        column_name: &str,
+        distinct_limit: Option<usize>,
+        detect_patterns: bool,
+    ) -> Self {
         let ValueNamesView { values, names } = names_and_values_view;
         let (column_index, column_name) = names
             .iter()
@@ -36,53 +197,125 @@ impl ColumnInfo {
         let mut number_of_strings: u32 = 0;
         let mut number_of_floats: u32 = 0;
         let mut number_of_ints: u32 = 0;
+        let mut int_min: Option<i128> = None;
+        let mut int_max: Option<i128> = None;
+        let mut stats = StreamingStats::default();
+        let mut track_int = |int: i128| {
+            number_of_ints += 1;
+            int_min = Some(int_min.map_or(int, |min| min.min(int)));
+            int_max = Some(int_max.map_or(int, |max| max.max(int)));
+        };
 
-        let mut values: Vec<&CsvAny> = values[column_index].iter().collect();
-        values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let mut pattern_counts = [0u32; Pattern::ALL.len()];
+        let mut hyper_log_log = HyperLogLog::default();
+        let mut heavy_hitters = HeavyHitters::default();
+        // `None` once there are more unique values than the limit
+        let mut exact: Option<HashMap<ValueKey, Seen>> = Some(HashMap::new());
+        let mut missing: HashMap<ValueKey, Seen> = HashMap::new();
+        for (row, x) in values[column_index].iter().enumerate() {
+            match x {
+                CsvAny::Str(s) => {
+                    number_of_strings += 1;
+                    if detect_patterns && let Some(pattern) = Pattern::detect(s) {
+                        pattern_counts[pattern as usize] += 1;
+                    }
+                }
+                CsvAny::Int(i) => {
+                    stats.push(*i as f64);
+                    track_int(i128::from(*i));
+                }
+                CsvAny::BigInt(i) => {
+                    stats.push(*i as f64);
+                    track_int(*i);
+                }
+                CsvAny::Float(f) => {
+                    stats.push(*f);
+                    number_of_floats += 1;
+                }
+                CsvAny::Null => number_of_nulls += 1,
+                CsvAny::Empty => number_of_empties += 1,
+            };
+            let key = ValueKey::from(x);
+            let seen = |uniques: &mut HashMap<ValueKey<'a>, Seen<'a>>, key| {
+                uniques.entry(key).or_insert(Seen { value: x, count: 0, first_row: row }).count += 1;
+            };
+            if matches!(key, ValueKey::Null | ValueKey::Empty) {
+                seen(&mut missing, key);
+                continue;
+            }
+            let Some(uniques) = exact.as_mut() else {
+                // the sketches only take over once the exact counts are dropped
+                hyper_log_log.push(&key);
+                match x {
+                    CsvAny::Str(s) => heavy_hitters.push(s),
+                    other => heavy_hitters.push(&other.to_string()),
+                }
+                continue;
+            };
+            seen(uniques, key);
+            if distinct_limit.is_some_and(|limit| uniques.len() > limit) {
+                let mut uniques = exact.take().into_iter().flatten().collect::<Vec<_>>();
+                uniques.sort_by_key(|(_, seen)| std::cmp::Reverse(seen.count));
+                for (key, seen) in uniques {
+                    hyper_log_log.push(&key);
+                    heavy_hitters.push_count(&seen.value.to_string(), seen.count);
+                }
+            }
+        }
+        let (distinct, top_values, mut values) = match exact {
+            Some(uniques) => {
+                let mut top_values = uniques
+                    .values()
+                    .map(|seen| ValueCount {
+                        value: seen.value.to_string(),
+                        count: seen.count,
+                    })
+                    .collect::<Vec<_>>();
+                top_values.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+                top_values.truncate(HEAVY_HITTERS_CAPACITY);
+                (Cardinality::Exact(uniques.len()), top_values, uniques.into_values().collect())
+            }
+            None => (
+                Cardinality::Approximate(hyper_log_log.estimate()),
+                heavy_hitters.top(),
+                Vec::new(),
+            ),
+        };
+        values.extend(missing.into_values());
+        let mut patterns = Pattern::ALL
+            .into_iter()
+            .zip(pattern_counts)
+            .filter(|(_, count)| *count > 0)
+            .map(|(pattern, count)| PatternShare {
+                pattern,
+                count,
+                share: f64::from(count) / f64::from(number_of_strings),
+            })
+            .collect::<Vec<_>>();
+        patterns.sort_by_key(|pattern_share| std::cmp::Reverse(pattern_share.count));
+        values.sort_by(|a, b| a.value.total_cmp(b.value));
 
         let unique_values = values
             .into_iter()
-            .inspect(|&x| {
-                match x {
-                    CsvAny::Str(_) => number_of_strings += 1,
-                    CsvAny::Int(_) => number_of_ints += 1,
-                    CsvAny::Float(_) => number_of_floats += 1,
-                    CsvAny::Null => number_of_nulls += 1,
-                    CsvAny::Empty => number_of_empties += 1,
-                };
-            })
-            .dedup_by(|a, b| a == b)
-            .cloned()
-            .map(|unique_val| match unique_val {
-                CsvAny::Str(str) => Variant {
-                    raw: str.clone(),
-                    sanitized: sanitize_identifier(&str),
-                    csvany: CsvAny::Str(str),
-                },
-                CsvAny::Int(i) => {
-                    let raw = i.to_string();
-                    let sanitized = sanitize_identifier(&raw);
-                    Variant {
-                        raw,
-                        sanitized,
-                        csvany: CsvAny::Int(i),
+            .map(|seen| {
+                let (raw, sanitized) = match seen.value {
+                    CsvAny::Str(str) => (str.clone(), sanitize_identifier(str)),
+                    CsvAny::Int(_) | CsvAny::BigInt(_) => {
+                        let raw = seen.value.to_string();
+                        let sanitized = sanitize_identifier(&raw);
+                        (raw, sanitized)
                     }
+                    CsvAny::Null => ("Null".to_string(), "Null".to_string()),
+                    CsvAny::Empty => ("Empty".to_string(), "Empty".to_string()),
+                    CsvAny::Float(f) => (f.to_string(), "".to_string()),
+                };
+                Variant {
+                    raw,
+                    sanitized,
+                    csvany: seen.value.clone(),
+                    count: seen.count,
+                    first_row: Some(seen.first_row),
                 }
-                CsvAny::Null => Variant {
-                    raw: "Null".to_string(),
-                    sanitized: "Null".to_string(),
-                    csvany: CsvAny::Null,
-                },
-                CsvAny::Empty => Variant {
-                    raw: "Empty".to_string(),
-                    sanitized: "Empty".to_string(),
-                    csvany: CsvAny::Empty,
-                },
-                CsvAny::Float(f) => Variant {
-                    raw: f.to_string(),
-                    sanitized: "".to_string(),
-                    csvany: CsvAny::Float(f),
-                },
             })
             .collect::<Vec<Variant>>();
 
@@ -94,36 +327,181 @@ impl ColumnInfo {
             number_of_floats,
             number_of_ints,
             unique_values,
+            markers: Vec::new(),
+            int_min,
+            int_max,
+            type_override: None,
+            stats: stats.finish(),
+            distinct,
+            top_values,
+            patterns,
+            semantic_type: None,
+        }
+    }
+
+    /// The kind of the column, `Null` and `Empty` values are ignored
+    /// and the type override, if any, always wins
+    pub fn kind(&self) -> ColumnKind {
+        if let Some(column_type) = self.type_override {
+            return column_type.into();
+        }
+        match (self.number_of_strings, self.number_of_ints, self.number_of_floats, self.semantic_type) {
+            (0, _, 0, _) => ColumnKind::Int(self.int_type()),
+            // whole numbers are valid floats
+            (0, _, _, _) => ColumnKind::Float,
+            (_, 0, 0, Some(pattern)) => ColumnKind::Semantic(pattern),
+            // too many unique values for an enum
+            _ if matches!(self.distinct, Cardinality::Approximate(_)) => ColumnKind::Text,
+            (_, 0, 0, None) => ColumnKind::Categorical,
+            _ => ColumnKind::Mixed,
+        }
+    }
+
+    /// Whether the column can identify the rows: every value is unique,
+    /// none is missing and the values can be `HashMap` keys.
+    /// Never true once the distinct count is approximate
+    pub fn is_candidate_key(&self) -> bool {
+        let cells = self.number_of_strings + self.number_of_ints + self.number_of_floats;
+        cells > 1
+            && !self.is_nullable()
+            && self.kind().is_hashable()
+            && self.distinct == Cardinality::Exact(cells as usize)
+    }
+
+    /// Whether the column contains `Null` or `Empty` values
+    pub fn is_nullable(&self) -> bool {
+        self.number_of_nulls + self.number_of_empties > 0
+    }
+
+    /// The narrowest rust integer type able to hold every integer
+    /// of the column, unsigned types are preferred when there are no negatives.
+    /// An `Int` override always wins
+    pub fn int_type(&self) -> &'static str {
+        if let Some(ColumnType::Int(int_type)) = self.type_override {
+            return int_type;
+        }
+        let (Some(min), Some(max)) = (self.int_min, self.int_max) else {
+            return "i64";
+        };
+        let fits = |lo: i128, hi: i128| lo <= min && max <= hi;
+        if min >= 0 {
+            [
+                ("u8", u8::MAX as i128),
+                ("u16", u16::MAX as i128),
+                ("u32", u32::MAX as i128),
+                ("u64", u64::MAX as i128),
+            ]
+            .into_iter()
+            .find(|&(_, hi)| fits(0, hi))
+            .map_or("i128", |(ty, _)| ty)
+        } else {
+            [
+                ("i8", i8::MIN as i128, i8::MAX as i128),
+                ("i16", i16::MIN as i128, i16::MAX as i128),
+                ("i32", i32::MIN as i128, i32::MAX as i128),
+                ("i64", i64::MIN as i128, i64::MAX as i128),
+            ]
+            .into_iter()
+            .find(|&(_, lo, hi)| fits(lo, hi))
+            .map_or("i128", |(ty, _, _)| ty)
         }
     }
-    
 }
 
 impl Display for ColumnInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let render = [
-            (self.number_of_empties, "Empties"),
-            (self.number_of_nulls, "Nulls"),
-            (self.number_of_strings, "Strings"),
-            (self.number_of_floats, "Floats"),
-            (self.number_of_ints, "Ints"),
-        ]
-        .into_iter()
-        .map(|(x, str)| match x {
-            0 => "".to_string(),
-            n => format!("\n\t{str}: {n}"),
-        })
-        .collect::<String>();
+        let render = render_type_counts(
+            self.number_of_empties,
+            self.number_of_nulls,
+            self.number_of_strings,
+            self.number_of_floats,
+            self.number_of_ints,
+        );
 
         let unique_values = self
             .unique_values
             .iter()
             .map(|x| format!("\n\t{:?}", x))
             .collect::<String>();
+        let markers = match self.markers.as_slice() {
+            [] => "".to_string(),
+            markers => format!("\n\nMarkers: {}", markers.iter().join(" ")),
+        };
+        let int_range = match (self.int_min, self.int_max) {
+            (Some(min), Some(max)) => format!("\n\nInt range: {min}..={max} ({})", self.int_type()),
+            _ => "".to_string(),
+        };
+        let type_override = match self.type_override {
+            Some(column_type) => format!("\n\nType override: {column_type}"),
+            None => "".to_string(),
+        };
+        let stats = match &self.stats {
+            Some(stats) => format!("\n\nStats: {stats}"),
+            None => "".to_string(),
+        };
+        let top_values = self.top_values.iter().take(5).map(ToString::to_string).join(", ");
+        let patterns = match self.patterns.as_slice() {
+            [] => "".to_string(),
+            patterns => format!("\nPatterns: {}", patterns.iter().join(", ")),
+        };
         write!(
             f,
-            "Name: {}\n\nTypes:{}\n\nUnique Values:{}",
-            self.column_name.sanitized.0, render, unique_values
+            "Name: {}\n\nTypes:{}{}{}{}{}\n\nDistinct: {}\nTop values: {}{}\n\nUnique Values:{}",
+            self.column_name.sanitized.0,
+            render,
+            markers,
+            int_range,
+            type_override,
+            stats,
+            self.distinct,
+            top_values,
+            patterns,
+            unique_values
         )
     }
 }
+
+/// A `\n\t<type>: <count>` line for each type found in a column,
+/// also used by the `Display` of `ColumnProfile`
+pub(crate) fn render_type_counts(empties: u32, nulls: u32, strings: u32, floats: u32, ints: u32) -> String {
+    [
+        (empties, "Empties"),
+        (nulls, "Nulls"),
+        (strings, "Strings"),
+        (floats, "Floats"),
+        (ints, "Ints"),
+    ]
+    .into_iter()
+    .map(|(x, str)| match x {
+        0 => "".to_string(),
+        n => format!("\n\t{str}: {n}"),
+    })
+    .collect::<String>()
+}
+
+/// Pairs of columns whose values together are unique, see `ColumnInfo::is_candidate_key`.
+/// Columns which are keys on their own are not paired, every pair with them would be unique
+pub fn composite_keys(values: &[Vec<CsvAny>], infos: &[ColumnInfo]) -> Vec<(usize, usize)> {
+    let candidates = infos
+        .iter()
+        .enumerate()
+        .filter(|(_, info)| !info.is_candidate_key() && !info.is_nullable() && info.kind().is_hashable())
+        .map(|(column_index, _)| column_index)
+        .collect::<Vec<_>>();
+    candidates
+        .into_iter()
+        .tuple_combinations()
+        .filter(|&(a, b)| {
+            let rows = values[a].len();
+            // not enough combinations of values for every row
+            if rows < 2 || infos[a].distinct.count().saturating_mul(infos[b].distinct.count()) < rows {
+                return false;
+            }
+            let mut seen = HashSet::with_capacity(rows);
+            values[a]
+                .iter()
+                .zip(&values[b])
+                .all(|(x, y)| seen.insert((ValueKey::from(x), ValueKey::from(y))))
+        })
+        .collect()
+}
@@ -1,18 +1,52 @@
 #![allow(clippy::uninlined_format_args)]
 
-use crate::{COLUMN_TYPE_ENUM_NAME,ColName, CsvAny, csv_dataset::CsvDataset, dataset_info::{ColumnInfo, Variant}, sanitizer::sanitize_identifier};
+use std::collections::BTreeSet;
+
+use itertools::Itertools;
+
+use crate::{dataset_info::ColumnKind, gen_options::{EmptyHandling, GenOptions}, patterns::Pattern, schema::{Category, Schema, SchemaColumn}};
 
 
 #[macro_export]
 macro_rules! create_enum {
     // --- 1. Caso Base: Input finito ---
     // Quando la lista di input `[]` è vuota, generiamo il codice finale.
-    (@step $name:ident, ($($variants:ident),*), ($($arms:tt)*), []) => {
+    // Ogni variante è accumulata insieme alla stringa originale del csv.
+    (@step $name:ident, [$(#[$meta:meta])* $vis:vis], closed, ($([$(#[$vmeta:meta])*] $variants:ident => $raws:expr,)*), ($($arms:tt)*), []) => {
         
         
-        #[derive(Debug, PartialEq, Clone, Copy)]
-        pub enum $name {
-            $($variants),*
+        #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy)]
+        $(#[$meta])*
+        $vis enum $name {
+            $($(#[$vmeta])* $variants),*
+        }
+
+        impl $name {
+            /// Tutte le varianti, nell'ordine di `ordinal`
+            pub const ALL: [Self; [$(stringify!($variants)),*].len()] = [$($name::$variants),*];
+            pub const COUNT: usize = Self::ALL.len();
+
+            /// La stringa originale del csv
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variants => $raws,)*
+                }
+            }
+
+            /// La posizione della variante in `ALL`
+            pub fn ordinal(&self) -> usize {
+                *self as usize
+            }
+
+            pub fn from_ordinal(ordinal: usize) -> Option<Self> {
+                Self::ALL.get(ordinal).copied()
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
         }
 
         impl std::str::FromStr for $name {
@@ -21,156 +55,502 @@ macro_rules! create_enum {
                 match s {
                     // Inseriamo qui tutti i rami match accumulati
                     $($arms)*
-                    _ => Err(format!("Valore non riconosciuto: '{}'", s)),
+                    _ => Err(format!("Unknown {} value: '{}'", stringify!($name), s)),
                 }
             }
         }
         
     };
+    // Caso Base con `Other`: i valori sconosciuti finiscono in `Other(String)`
+    (@step $name:ident, [$(#[$meta:meta])* $vis:vis], [other $(#[$ometa:meta])*], ($([$(#[$vmeta:meta])*] $variants:ident => $raws:expr,)*), ($($arms:tt)*), []) => {
+
+        #[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone)]
+        $(#[$meta])*
+        $vis enum $name {
+            $($(#[$vmeta])* $variants,)*
+            $(#[$ometa])*
+            Other(String),
+        }
+
+        impl $name {
+            /// Tutte le varianti conosciute, `Other` escluso
+            pub const ALL: [Self; [$(stringify!($variants)),*].len()] = [$($name::$variants),*];
+            pub const COUNT: usize = Self::ALL.len();
+
+            /// Whether the value was not known when the enum was generated
+            pub fn is_other(&self) -> bool {
+                matches!(self, $name::Other(_))
+            }
+
+            /// La stringa originale del csv
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variants => $raws,)*
+                    $name::Other(raw) => raw,
+                }
+            }
+
+            /// La posizione della variante in `ALL`, `None` per `Other`
+            pub fn ordinal(&self) -> Option<usize> {
+                Self::ALL.iter().position(|variant| variant == self)
+            }
+
+            pub fn from_ordinal(ordinal: usize) -> Option<Self> {
+                Self::ALL.get(ordinal).cloned()
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        impl std::str::FromStr for $name {
+            type Err = String;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                match s {
+                    $($arms)*
+                    _ => Ok($name::Other(s.to_string())),
+                }
+            }
+        }
+    };
 
     // --- 2. Caso Complesso: "str1" | "str2" => Variante ---
-    // Riconosce il pattern stringa/e => Identificatore
-    (@step $name:ident, ($($vars:ident),*), ($($arms:tt)*), [ $($l:literal)|+ => $v:ident, $($rest:tt)* ]) => {
+    // Riconosce il pattern stringa/e => Identificatore,
+    // la prima stringa è quella restituita da `as_str`.
+    // Gli attributi prima della variante (es. `#[serde(..)]`) vengono copiati su di essa
+    (@step $name:ident, $meta:tt, $mode:tt, ($($vars:tt)*), ($($arms:tt)*), [ $(#[$vm:meta])* $first:literal $(| $l:literal)* => $v:ident, $($rest:tt)* ]) => {
         create_enum!(
             @step 
-            $name, 
-            ($($vars,)* $v), // Aggiunge la variante alla lista
-            ($($arms)* $($l)|+ => Ok($name::$v),), // Aggiunge il match arm personalizzato
+            $name, $meta, $mode,
+            ($($vars)* [$(#[$vm])*] $v => $first,), // Aggiunge la variante alla lista
+            ($($arms)* $first $(| $l)* => Ok($name::$v),), // Aggiunge il match arm personalizzato
             [ $($rest)* ] // Continua con il resto
         );
     };
     // Gestione dell'ultimo elemento (senza virgola finale) per il caso complesso
-    (@step $name:ident, ($($vars:ident),*), ($($arms:tt)*), [ $($l:literal)|+ => $v:ident ]) => {
-        create_enum!(@step $name, ($($vars,)* $v), ($($arms)* $($l)|+ => Ok($name::$v),), []);
+    (@step $name:ident, $meta:tt, $mode:tt, ($($vars:tt)*), ($($arms:tt)*), [ $(#[$vm:meta])* $first:literal $(| $l:literal)* => $v:ident ]) => {
+        create_enum!(@step $name, $meta, $mode, ($($vars)* [$(#[$vm])*] $v => $first,), ($($arms)* $first $(| $l)* => Ok($name::$v),), []);
     };
 
     // --- 3. Caso Semplice: Variante ---
     // Riconosce solo l'Identificatore (usa il nome stesso come stringa)
-    (@step $name:ident, ($($vars:ident),*), ($($arms:tt)*), [ $v:ident, $($rest:tt)* ]) => {
+    (@step $name:ident, $meta:tt, $mode:tt, ($($vars:tt)*), ($($arms:tt)*), [ $(#[$vm:meta])* $v:ident, $($rest:tt)* ]) => {
         create_enum!(
             @step 
-            $name, 
-            ($($vars,)* $v), 
+            $name, $meta, $mode,
+            ($($vars)* [$(#[$vm])*] $v => stringify!($v),), 
             ($($arms)* stringify!($v) => Ok($name::$v),), 
             [ $($rest)* ]
         );
     };
     // Gestione dell'ultimo elemento (senza virgola finale) per il caso semplice
-    (@step $name:ident, ($($vars:ident),*), ($($arms:tt)*), [ $v:ident ]) => {
-        create_enum!(@step $name, ($($vars,)* $v), ($($arms)* stringify!($v) => Ok($name::$v),), []);
+    (@step $name:ident, $meta:tt, $mode:tt, ($($vars:tt)*), ($($arms:tt)*), [ $(#[$vm:meta])* $v:ident ]) => {
+        create_enum!(@step $name, $meta, $mode, ($($vars)* [$(#[$vm])*] $v => stringify!($v),), ($($arms)* stringify!($v) => Ok($name::$v),), []);
     };
 
     // --- Entry Point ---
-    // Inizializza gli accumulatori vuoti
-    ($name:ident; $($input:tt)*) => {
-        create_enum!(@step $name, (), (), [ $($input)* ]);
+    // Inizializza gli accumulatori vuoti,
+    // gli attributi (es. `#[non_exhaustive]`) vengono copiati sull'enum.
+    // Senza visibilità l'enum è `pub`
+    ($(#[$meta:meta])* $name:ident; $($input:tt)*) => {
+        create_enum!(@step $name, [$(#[$meta])* pub], closed, (), (), [ $($input)* ]);
+    };
+    // `create_enum!(name, Other; ..)` aggiunge la variante `Other(String)`,
+    // anche lei può avere degli attributi: `create_enum!(name, #[serde(untagged)] Other; ..)`
+    ($(#[$meta:meta])* $name:ident, $(#[$ometa:meta])* Other; $($input:tt)*) => {
+        create_enum!(@step $name, [$(#[$meta])* pub], [other $(#[$ometa])*], (), (), [ $($input)* ]);
+    };
+    // Con la visibilità: `create_enum!(pub(crate) name; ..)`
+    ($(#[$meta:meta])* $vis:vis $name:ident; $($input:tt)*) => {
+        create_enum!(@step $name, [$(#[$meta])* $vis], closed, (), (), [ $($input)* ]);
+    };
+    ($(#[$meta:meta])* $vis:vis $name:ident, $(#[$ometa:meta])* Other; $($input:tt)*) => {
+        create_enum!(@step $name, [$(#[$meta])* $vis], [other $(#[$ometa])*], (), (), [ $($input)* ]);
     };
 }
 
 
 
 
-pub fn generate_enums_from(dataset: &mut CsvDataset) -> String{
+/// The enums of every column of `schema` and the `CsvColumn` view over them
+pub fn generate_enums_from(schema: &Schema, options: &GenOptions) -> String{
+    let column_enum = &options.names.column;
     
     let mut full_string = String::new();
 
-    let (value_names_view, info) = dataset.split_view_and_info();
-    let col_name = value_names_view.names;
-    let enums = col_name.iter().map(|col_name| {
-
-        let mut col_info = ColumnInfo::new(value_names_view, &col_name.raw);
+    let enums = schema.columns.iter().map(|column| -> String {
 
-
-        if !col_info.unique_values.iter().any(|x| x.csvany == CsvAny::Null){
-            let str = String::from("Null");
-            col_info.unique_values.push(Variant{ raw: str.clone(), sanitized: str, csvany: CsvAny::Null});
+        if options.shared_enums.contains(&column.ident) {
+            return format!("use super::{};", column.ident);
         }
-
-        info.push(col_info.clone());
-        
-        let unique_val_iter = col_info.unique_values.iter();
-
-        
-        let is_str = unique_val_iter.clone().all(|x| {
-            matches!(x.csvany, CsvAny::Str(_) | CsvAny::Empty | CsvAny::Null)
-        });
-        let is_int = unique_val_iter.clone().all(|x| {
-            matches!(x.csvany, CsvAny::Int(_) | CsvAny::Empty | CsvAny::Null)
-        });
-        let is_float = unique_val_iter.clone().all(|x| {
-            matches!(x.csvany, CsvAny::Float(_) | CsvAny::Empty | CsvAny::Null)
-        });
-        
-        if is_int{
-            gen_int_enum(col_name)
-        }else if is_float{
-            gen_float_enum(col_name)
-        }
-        else if is_str{
-            gen_str_enum(col_name, unique_val_iter)
-        }else {
-            println!("enum generation log: column `{}` contains numbers and strings", col_name.raw);
-            gen_str_enum(col_name, unique_val_iter)
+        match column.kind {
+            // numbers and text are plain `Option<T>` in option mode
+            ColumnKind::Int(_) | ColumnKind::Float | ColumnKind::Text | ColumnKind::Semantic(_) if options.option_columns => String::new(),
+            ColumnKind::Int(int_type) => gen_numeric_doc(column, options) + &gen_int_enum(column, int_type, options),
+            ColumnKind::Float => gen_numeric_doc(column, options) + &gen_float_enum(column, options),
+            ColumnKind::Text => gen_text_enum(column, options),
+            ColumnKind::Semantic(pattern) => gen_semantic_enum(column, pattern, options),
+            ColumnKind::Categorical => gen_str_enum(column, options),
+            ColumnKind::Mixed => {
+                // stdout is reserved to the generated code
+                eprintln!("enum generation log: column `{}` contains numbers and strings", column.name);
+                gen_mixed_enum(column, options)
+            }
         }
         
-    } + "\n\n").collect::<String>();
+    }).map(|enum_code| enum_code + "\n\n").collect::<String>();
 
     
-    let mut columns_enum = format!("#[derive(Debug)]\npub enum {COLUMN_TYPE_ENUM_NAME}{{\n");
+    // a borrowed view over any column, see `get_columns`
+    let mut columns_enum = format!("#[derive(Debug, Clone, Copy)]\n{} enum {column_enum}<'a>{{\n", options.visibility);
 
-    for col_name in col_name.iter() {
-        let sanitized = &col_name.sanitized.0;
-        columns_enum.push_str(&format!("{sanitized}(Vec<{sanitized}>),\n"));
+    for column in schema.columns.iter() {
+        let element_type = options.element_type(column);
+        columns_enum.push_str(&format!("{}(&'a [{element_type}]),\n", column.ident));
     }
     columns_enum.push_str("}\n\n");
 
-    
-
     full_string.push_str(&enums);
     full_string.push_str(&columns_enum);
     // full_string.push_str(&columns_enum_from_str);
     full_string
 }
 
-fn gen_str_enum<'a>(col_name: &ColName, unique_values: impl Iterator<Item = &'a Variant>) -> String{
-    let variants = unique_values
-            .map(|var| match &var.csvany {
-                CsvAny::Int(int) => sanitize_identifier(&int.to_string()),
-                CsvAny::Str(str) => format!("\"{}\" => {}", str, sanitize_identifier(str)),
-                CsvAny::Empty => "Empty".to_string(),
-                CsvAny::Null => "Null".to_string(),
-                CsvAny::Float(_) => panic!("Should not be used on float since they cannot represent categories"),
-            } + ",\n")
+/// Categorical columns with the same name in more schemas and at least
+/// a category in common in all of them. Each one is merged in a single `SchemaColumn`
+/// holding every category, to be generated once with `gen_shared_enums`
+pub fn shared_columns(schemas: &[&Schema]) -> Vec<SchemaColumn> {
+    let mut shared: Vec<SchemaColumn> = Vec::new();
+    for column in schemas.iter().flat_map(|schema| &schema.columns) {
+        if shared.iter().any(|merged| merged.ident == column.ident) {
+            continue;
+        }
+        let same_name = schemas
+            .iter()
+            .filter_map(|schema| schema.columns.iter().find(|x| x.ident == column.ident))
+            .collect::<Vec<&SchemaColumn>>();
+        if same_name.len() > 1 {
+            shared.extend(merge_categories(&same_name));
+        }
+    }
+    shared
+}
+
+/// `None` if the columns are not all categorical, have no category in common
+/// or two different categories would get the same variant name
+fn merge_categories(columns: &[&SchemaColumn]) -> Option<SchemaColumn> {
+    if columns.iter().any(|column| column.kind != ColumnKind::Categorical) {
+        return None;
+    }
+    let raws = |column: &&SchemaColumn| {
+        column.categories
+            .iter()
+            .map(|category| category.raw.clone())
+            .collect::<BTreeSet<String>>()
+    };
+    let common = columns
+        .iter()
+        .map(raws)
+        .reduce(|common, other| common.intersection(&other).cloned().collect())?;
+    if common.is_empty() {
+        return None;
+    }
+
+    let mut categories = columns
+        .iter()
+        .flat_map(|column| column.categories.iter().cloned())
+        .collect::<Vec<Category>>();
+    categories.sort_by(|a, b| a.raw.cmp(&b.raw));
+    categories.dedup_by(|a, b| a.raw == b.raw);
+    if !categories.iter().map(|category| &category.ident).all_unique() {
+        return None;
+    }
+
+    Some(SchemaColumn {
+        nullable: columns.iter().any(|column| column.nullable),
+        categories,
+        ..columns[0].clone()
+    })
+}
+
+/// The enums of the columns found by `shared_columns`,
+/// the code of each file imports them with `GenOptions::shared_enums`
+pub fn gen_shared_enums(shared: &[SchemaColumn], options: &GenOptions) -> String {
+    shared
+        .iter()
+        .map(|column| gen_str_enum(column, options) + "\n\n")
+        .collect()
+}
+
+fn gen_str_enum(column: &SchemaColumn, options: &GenOptions) -> String{
+    let mut variants = column.categories
+            .iter()
+            .map(|Category { raw, ident }| format!("{}{raw:?} => {ident},\n", serde_rename(raw, options)))
             .collect::<String>();
-        format!("create_enum!({};\n{variants});", col_name.sanitized.0)
+        if options.empty_handling == EmptyHandling::Distinct && !options.option_columns {
+            variants.push_str(&format!("{}\"\" => Empty,\n", serde_rename("", options)));
+        }
+        // after the renamed variants, serde wants the untagged ones last
+        if !options.option_columns {
+            variants.push_str(&format!("{}Null,\n", serde_untagged(options)));
+        }
+        let non_exhaustive = match options.non_exhaustive {
+            true => "#[non_exhaustive] ",
+            false => "",
+        };
+        // unknown categories are serialized as the raw string
+        let other = match options.other_variant {
+            true => format!(", {}Other", serde_untagged(options)),
+            false => String::new(),
+        };
+        let serde_derive = options.serde_derive();
+        let vis = options.visibility;
+        format!("create_enum!({serde_derive}{non_exhaustive}{vis} {}{other};\n{variants});", column.ident)
 }
-fn gen_float_enum(col_name: &ColName) -> String {
-    let name = &col_name.sanitized.0;
+
+/// A column with numbers and strings, numbers are kept in `Int` and `Float(f64)`
+/// and every string gets its own variant like in `gen_str_enum`.
+/// `Int` holds the narrowest integer type, like `gen_int_enum`
+fn gen_mixed_enum(column: &SchemaColumn, options: &GenOptions) -> String {
+    let name = &column.ident;
+    let int_type = column.mixed_int_type();
+    let mut variants = column.categories
+        .iter()
+        .map(|Category { raw, ident }| format!("{}{ident},\n", serde_rename(raw, options)))
+        .collect::<String>();
+    let mut arms = column.categories
+        .iter()
+        .map(|Category { raw, ident }| format!("{raw:?} => Ok({name}::{ident}),\n"))
+        .collect::<String>();
+    let mut str_arms = column.categories
+        .iter()
+        .map(|Category { raw, ident }| format!("{name}::{ident} => std::borrow::Cow::Borrowed({raw:?}),\n"))
+        .collect::<String>();
+    // numbers and `Null` are written bare, they must come after the renamed variants
+    let untagged = serde_untagged(options);
+    if !options.option_columns {
+        if options.empty_handling == EmptyHandling::Distinct {
+            variants.push_str(&format!("{}Empty,\n", serde_rename("", options)));
+            str_arms.push_str(&format!("{name}::Empty => std::borrow::Cow::Borrowed(\"\"),\n"));
+        }
+        variants.push_str(&format!("{untagged}Null,\n"));
+        str_arms.push_str(&format!("{name}::Null => std::borrow::Cow::Borrowed(\"Null\"),\n"));
+        arms.push_str(&format!("\"\" => Ok({name}::{}),\n", options.empty_variant()));
+    }
+    variants.push_str(&format!("{untagged}Int({int_type}),\n{untagged}Float(f64),\n"));
+    str_arms.push_str(&format!(
+        "{name}::Int(i) => std::borrow::Cow::Owned(i.to_string()),\n{name}::Float(f) => std::borrow::Cow::Owned(f.to_string()),\n"
+    ));
+
+    let (copy, unknown, is_other) = match options.other_variant {
+        true => {
+            variants.push_str(&format!("{untagged}Other(String),\n"));
+            str_arms.push_str(&format!("{name}::Other(raw) => std::borrow::Cow::Borrowed(raw),\n"));
+            (
+                "",
+                format!("Ok(number.unwrap_or_else(|_| {name}::Other(s.to_string())))"),
+                format!(
+                    "
+        /// Whether the value was not known when the enum was generated
+        pub fn is_other(&self) -> bool {{
+            matches!(self, {name}::Other(_))
+        }}
+"
+                ),
+            )
+        }
+        false => (
+            " Copy,",
+            format!("number.map_err(|_| format!(\"Unknown {name} value: '{{s}}'\"))"),
+            String::new(),
+        ),
+    };
+    let number = format!(
+        "let number = s
+                .parse::<{int_type}>()
+                .map({name}::Int)
+                .or_else(|_| s.parse::<f64>().map({name}::Float));
+            {unknown}"
+    );
+    // a column with only numbers in option mode has no named variant to match
+    let body = match arms.is_empty() {
+        true => number,
+        false => format!("match s {{\n{arms}_ => {{\n{number}\n}}\n}}"),
+    };
+    let non_exhaustive = match options.non_exhaustive {
+        true => "#[non_exhaustive]\n",
+        false => "",
+    };
+    let serde_derive = options.serde_derive();
+    let vis = options.visibility;
+    format!("
+    #[derive(Debug, Clone,{copy} PartialEq, PartialOrd)]
+{serde_derive}{non_exhaustive}    {vis} enum {name} {{
+{variants}    }}
+
+    impl {name} {{
+        /// The raw csv string, numbers are written back
+        pub fn as_str(&self) -> std::borrow::Cow<'_, str> {{
+            match self {{
+{str_arms}            }}
+        }}
+{is_other}    }}
+
+    impl std::fmt::Display for {name} {{
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{
+            f.write_str(&self.as_str())
+        }}
+    }}
+
+    impl std::str::FromStr for {name}{{
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {{
+            {body}
+        }}
+    }}")
+}
+
+/// Serialize the variant with its raw csv spelling
+fn serde_rename(raw: &str, options: &GenOptions) -> String {
+    match options.serde {
+        true => format!("#[serde(rename = {raw:?})] "),
+        false => String::new(),
+    }
+}
+
+/// Serialize the variant as its bare value, `null` for a unit variant
+fn serde_untagged(options: &GenOptions) -> &'static str {
+    match options.serde {
+        true => "#[serde(untagged)] ",
+        false => "",
+    }
+}
+
+/// Document the units stripped from the values of a numeric column
+/// and, with `GenOptions::stats_docs`, its statistics.
+/// It goes on the enum, or on the frame field in option mode
+pub(crate) fn gen_numeric_doc(column: &SchemaColumn, options: &GenOptions) -> String {
+    let markers = match column.markers.as_slice() {
+        [] => String::new(),
+        markers => format!(
+            "\n    /// Unit: `{}` (stripped from the csv values)",
+            markers.iter().join("`, `")
+        ),
+    };
+    let stats = match &column.stats {
+        Some(stats) if options.stats_docs => format!("\n    /// Stats: {stats}"),
+        _ => String::new(),
+    };
+    markers + &stats
+}
+
+/// Numbers and text are serialized as the bare value, missing values as `null`
+fn untagged_serde(options: &GenOptions) -> String {
+    match options.serde {
+        true => format!("{}#[serde(untagged)]\n", options.serde_derive()),
+        false => String::new(),
+    }
+}
+
+fn gen_text_enum(column: &SchemaColumn, options: &GenOptions) -> String {
+    let vis = options.visibility;
+    let name = &column.ident;
+    let serde = untagged_serde(options);
+    let missing = options.missing_variants();
+    let empty = options.empty_variant();
+    format!("
+    #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd)]
+{serde}    {vis} enum {name} {{ Text(String), {missing} }}
+
+    impl std::str::FromStr for {name}{{
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {{ 
+            if s.is_empty() {{
+                return Ok({name}::{empty});
+            }}
+            Ok({name}::Text(s.to_string()))
+        }}
+    }}")
+}
+
+/// The value is held by the validated type of the pattern, e.g. `Email(csv_deserializer::patterns::Email)`
+fn gen_semantic_enum(column: &SchemaColumn, pattern: Pattern, options: &GenOptions) -> String {
+    let vis = options.visibility;
+    let name = &column.ident;
+    let serde = untagged_serde(options);
+    let missing = options.missing_variants();
+    let empty = options.empty_variant();
+    let variant = pattern.variant();
+    let rust_type = pattern.rust_type();
+    let copy = match pattern.is_copy() {
+        true => " Copy,",
+        false => "",
+    };
+    format!("
+    #[derive(Debug, Clone,{copy} PartialEq, Eq, Hash, PartialOrd)]
+{serde}    {vis} enum {name} {{ {variant}({rust_type}), {missing} }}
+
+    impl std::str::FromStr for {name}{{
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {{ 
+            if s.is_empty() {{
+                return Ok({name}::{empty});
+            }}
+            s.parse::<{rust_type}>()
+                .map({name}::{variant})
+                .map_err(|e| format!(\"Invalid {name} value '{{s}}': {{e}}\"))
+        }}
+    }}")
+}
+
+fn gen_float_enum(column: &SchemaColumn, options: &GenOptions) -> String {
+    let vis = options.visibility;
+    let name = &column.ident;
+    let serde = untagged_serde(options);
+    let missing = options.missing_variants();
+    let empty = options.empty_variant();
     format!("
     #[derive(Debug, Clone, Copy, PartialEq,PartialOrd)]
-    pub enum {name} {{ Float(f64), Null }}
+{serde}    {vis} enum {name} {{ Float(f64), {missing} }}
 
     impl std::str::FromStr for {name}{{
         type Err = String;
 
         fn from_str(s: &str) -> Result<Self, Self::Err> {{ 
-            let f = s.parse::<f64>().unwrap();
+            if s.is_empty() {{
+                return Ok({name}::{empty});
+            }}
+            let f = s.parse::<f64>().map_err(|e| format!(\"Invalid {name} value '{{s}}': {{e}}\"))?;
             Ok({name}::Float(f))
         }}
     }}")
 }
 
-fn gen_int_enum(col_name: &ColName) -> String {
-    let name = &col_name.sanitized.0;
+/// `int_type` is the rust integer held by the `Int` variant, see `ColumnInfo::int_type`
+fn gen_int_enum(column: &SchemaColumn, int_type: &str, options: &GenOptions) -> String {
+    let vis = options.visibility;
+    let name = &column.ident;
+    let serde = untagged_serde(options);
+    let missing = options.missing_variants();
+    let empty = options.empty_variant();
     format!("
-    #[derive(Debug, Clone, Copy, PartialEq,PartialOrd)]
-    pub enum {name} {{ Int(i64), Null }}
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd)]
+{serde}    {vis} enum {name} {{ Int({int_type}), {missing} }}
 
     impl std::str::FromStr for {name}{{
         type Err = String;
 
         fn from_str(s: &str) -> Result<Self, Self::Err> {{ 
-            let i = s.parse::<i64>().unwrap();
+            if s.is_empty() {{
+                return Ok({name}::{empty});
+            }}
+            let i = s.parse::<{int_type}>().map_err(|e| format!(\"Invalid {name} value '{{s}}': {{e}}\"))?;
             Ok({name}::Int(i))
         }}
     }}
@@ -191,4 +571,112 @@ impl std::str::FromStr for {COLUMN_TYPE_ENUM_NAME}{{
     });
     // last case
     columns_enum_from_str.push_str("_ => Err(format!(\"Unknown string: '{}'\", s)),\n");
-    columns_enum_from_str.push_str("}\n}\n}"); */
\ No newline at end of file
+    columns_enum_from_str.push_str("}\n}\n}"); */
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use crate::{NullValues, csv_dataset::CsvDataset, gen_options::GenOptions, schema::Schema};
+
+    use super::{generate_enums_from, shared_columns};
+
+    // the enum generated for the `code` column of `mixed_columns_keep_numbers_apart`
+    #[allow(non_camel_case_types)]
+    mod mixed {
+        include!("fixtures/mixed_column.rs");
+    }
+
+    #[test]
+    fn mixed_columns_keep_numbers_apart() {
+        use mixed::code;
+
+        let rdr = csv::ReaderBuilder::new().from_reader("code,qty\n1,3\n2.5,4\nfoo,NA\nInt,5\n".as_bytes());
+        let schema = Schema::from_dataset(&CsvDataset::new(rdr, NullValues(vec!["NA"])));
+        assert_eq!(schema.columns[0].mixed_int_type(), "u8");
+        let generated = generate_enums_from(&schema, &GenOptions::default());
+        assert!(generated.contains("Int_,\nfoo,\nNull,\nInt(u8),\nFloat(f64),\n"), "{generated}");
+        assert!(generated.contains(".parse::<u8>()\n                .map(code::Int)"), "{generated}");
+
+        for value in [code::Int(1), code::Float(2.5), code::foo, code::Int_] {
+            assert_eq!(code::from_str(&value.as_str()), Ok(value));
+        }
+        assert_eq!(code::Int_.to_string(), "Int");
+        assert_eq!(code::from_str("200"), Ok(code::Int(200)));
+        assert_eq!(code::from_str("300"), Ok(code::Float(300.0)));
+    }
+
+    #[test]
+    fn related_files_share_categories() {
+        let dataset = |data: &'static str| {
+            let rdr = csv::ReaderBuilder::new().from_reader(data.as_bytes());
+            Schema::from_dataset(&CsvDataset::new(rdr, NullValues(vec![])))
+        };
+        let orders = dataset("status,kind\npaid,a\nshipped,b\n");
+        let returns = dataset("status,kind\nshipped,x\nrefunded,y\n");
+        let shared = shared_columns(&[&orders, &returns]);
+        // `kind` has no category in common
+        assert_eq!(shared.len(), 1);
+        let raws = shared[0].categories.iter().map(|category| category.raw.as_str()).collect::<Vec<_>>();
+        assert_eq!(raws, ["paid", "refunded", "shipped"]);
+    }
+
+    create_enum!(#[non_exhaustive] Species, Other;
+        "Iris-setosa" => IrisSetosa,
+        Null,
+    );
+
+    #[test]
+    fn unknown_categories_fall_into_other() {
+        assert_eq!(Species::from_str("Iris-setosa"), Ok(Species::IrisSetosa));
+        assert_eq!(Species::from_str("Null"), Ok(Species::Null));
+        let unknown = Species::from_str("Iris-virginica").unwrap();
+        assert_eq!(unknown, Species::Other("Iris-virginica".to_string()));
+        assert!(unknown.is_other());
+        assert_eq!(unknown.as_str(), "Iris-virginica");
+        assert_eq!(unknown.ordinal(), None);
+        assert_eq!(Species::COUNT, 2);
+        assert_eq!(Species::from_ordinal(1), Some(Species::ALL[1].clone()));
+    }
+
+    create_enum!(Color; "light blue" | "azure" => LightBlue, Red);
+
+    #[test]
+    fn variants_keep_the_raw_string() {
+        assert_eq!(Color::ALL, [Color::LightBlue, Color::Red]);
+        assert_eq!(Color::COUNT, 2);
+        assert_eq!(Color::from_str("azure"), Ok(Color::LightBlue));
+        assert_eq!(Color::LightBlue.to_string(), "light blue");
+        assert_eq!(Color::Red.as_str(), "Red");
+        assert_eq!(Color::Red.ordinal(), 1);
+        assert_eq!(Color::from_ordinal(1), Some(Color::Red));
+        assert!(Color::from_str("blue").is_err());
+    }
+
+    create_enum!(#[derive(serde::Serialize, serde::Deserialize)] Size; #[serde(rename = "S")] "S" => Small, #[serde(untagged)] Null);
+
+    #[test]
+    fn missing_categories_are_serialized_as_null() {
+        assert_eq!(serde_json::to_string(&[Size::Small, Size::Null]).unwrap(), r#"["S",null]"#);
+        assert_eq!(serde_json::from_str::<Size>("null").unwrap(), Size::Null);
+        assert_eq!(Size::from_ordinal(Size::Null.ordinal()), Some(Size::ALL[Size::COUNT - 1]));
+        let rdr = csv::ReaderBuilder::new().from_reader("species\nsetosa\nNA\n".as_bytes());
+        let schema = Schema::from_dataset(&CsvDataset::new(rdr, NullValues(vec!["NA"])));
+        let code = generate_enums_from(&schema, &GenOptions { serde: true, ..Default::default() });
+        assert!(code.contains("#[serde(rename = \"setosa\")] \"setosa\" => setosa,\n#[serde(untagged)] Null,"));
+    }
+
+    create_enum!(Quote; "say \"hi\"" => SayHi, "C:\\" => Drive, Null);
+
+    #[test]
+    fn quotes_in_categories_are_escaped() {
+        let rdr = csv::ReaderBuilder::new().from_reader("quote\n\"say \"\"hi\"\"\"\nC:\\\n".as_bytes());
+        let schema = Schema::from_dataset(&CsvDataset::new(rdr, NullValues(vec!["NA"])));
+        let code = generate_enums_from(&schema, &GenOptions { serde: true, ..Default::default() });
+        assert!(code.contains(r#"#[serde(rename = "say \"hi\"")] "say \"hi\"" =>"#), "{code}");
+        assert!(code.contains(r#"#[serde(rename = "C:\\")] "C:\\" =>"#), "{code}");
+        assert_eq!("say \"hi\"".parse::<Quote>().unwrap(), Quote::SayHi);
+        assert_eq!(Quote::Drive.as_str(), "C:\\");
+        assert_eq!(Quote::ALL[..Quote::COUNT - 1], [Quote::SayHi, Quote::Drive]);
+        assert_eq!(Quote::from_ordinal(Quote::Drive.ordinal()), Some(Quote::Drive));
+    }
+}
@@ -0,0 +1,46 @@
+    #[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+    pub enum code {
+Int_,
+foo,
+Null,
+Int(u8),
+Float(f64),
+    }
+
+    impl code {
+        /// The raw csv string, numbers are written back
+        pub fn as_str(&self) -> std::borrow::Cow<'_, str> {
+            match self {
+code::Int_ => std::borrow::Cow::Borrowed("Int"),
+code::foo => std::borrow::Cow::Borrowed("foo"),
+code::Null => std::borrow::Cow::Borrowed("Null"),
+code::Int(i) => std::borrow::Cow::Owned(i.to_string()),
+code::Float(f) => std::borrow::Cow::Owned(f.to_string()),
+            }
+        }
+    }
+
+    impl std::fmt::Display for code {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(&self.as_str())
+        }
+    }
+
+    impl std::str::FromStr for code{
+        type Err = String;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+"Int" => Ok(code::Int_),
+"foo" => Ok(code::foo),
+"" => Ok(code::Null),
+_ => {
+let number = s
+                .parse::<u8>()
+                .map(code::Int)
+                .or_else(|_| s.parse::<f64>().map(code::Float));
+            number.map_err(|_| format!("Unknown code value: '{s}'"))
+}
+}
+        }
+    }
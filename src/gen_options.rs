@@ -0,0 +1,190 @@
+use std::{fmt::Display, str::FromStr};
+
+use crate::{
+    COLUMN_TYPE_ENUM_NAME, ERROR_ENUM_NAME, MAIN_STRUCT_NAME, ROW_STRUCT_NAME,
+    dataset_info::ColumnKind,
+    schema::SchemaColumn,
+};
+
+/// Options shared by `enum_gen` and `struct_gen`
+#[derive(Debug, Clone, Default)]
+pub struct GenOptions {
+    pub empty_handling: EmptyHandling,
+    /// Generate `Vec<Option<T>>` columns instead of enums with a `Null` variant,
+    /// `Vec<T>` when the column has no nulls nor empties.
+    /// Numbers and text use `T` directly, categories use an enum without `Null`.
+    /// `Empty` is always merged into `None`
+    pub option_columns: bool,
+    /// Categorical enums get an `Other(String)` variant holding the values
+    /// not seen when generating, instead of failing on them
+    pub other_variant: bool,
+    /// Mark the categorical enums `#[non_exhaustive]`
+    pub non_exhaustive: bool,
+    /// Derive `Serialize` and `Deserialize` on the generated types,
+    /// categories keep their raw csv spelling and missing values are `null`.
+    /// The binary turns it on with `--serde`
+    pub serde: bool,
+    /// Names of the frame, row, column view and error types
+    pub names: TypeNames,
+    /// Wrap everything in `mod <name> { .. }`
+    pub module: Option<String>,
+    /// Visibility of the generated types and of the module
+    pub visibility: Visibility,
+    /// Document the statistics of the numeric columns on their enum, see `ColumnInfo::stats`
+    pub stats_docs: bool,
+    /// Sanitized names of the columns whose enum is shared with other files,
+    /// see `enum_gen::shared_columns`. They are imported with `use super::<name>`
+    pub shared_enums: Vec<String>,
+}
+
+/// Names of the top level generated types,
+/// by default `CsvDataFrame`, `CsvColumn`, `CsvRow` and `CsvDataFrameError`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeNames {
+    pub frame: String,
+    pub column: String,
+    pub row: String,
+    pub error: String,
+}
+
+impl Default for TypeNames {
+    fn default() -> Self {
+        Self {
+            frame: MAIN_STRUCT_NAME.to_string(),
+            column: COLUMN_TYPE_ENUM_NAME.to_string(),
+            row: ROW_STRUCT_NAME.to_string(),
+            error: ERROR_ENUM_NAME.to_string(),
+        }
+    }
+}
+
+impl TypeNames {
+    /// Names starting with `prefix` in PascalCase,
+    /// `iris` gives `IrisFrame`, `IrisColumn`, `IrisRow` and `IrisFrameError`
+    pub fn with_prefix(prefix: &str) -> Self {
+        let mut prefix = prefix
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .map(|word| {
+                let mut chars = word.chars();
+                chars
+                    .next()
+                    .map(|first| first.to_ascii_uppercase().to_string() + chars.as_str())
+                    .unwrap_or_default()
+            })
+            .collect::<String>();
+        if !prefix.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            prefix.insert_str(0, "Csv");
+        }
+        Self {
+            frame: format!("{prefix}Frame"),
+            column: format!("{prefix}Column"),
+            row: format!("{prefix}Row"),
+            error: format!("{prefix}FrameError"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Visibility {
+    #[default]
+    Pub,
+    Crate,
+}
+
+impl Display for Visibility {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Visibility::Pub => write!(f, "pub"),
+            Visibility::Crate => write!(f, "pub(crate)"),
+        }
+    }
+}
+
+impl FromStr for Visibility {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "pub" => Ok(Visibility::Pub),
+            "pub(crate)" | "crate" => Ok(Visibility::Crate),
+            other => Err(format!("Unknown visibility: '{other}', expected `pub` or `pub(crate)`")),
+        }
+    }
+}
+
+/// How `CsvAny::Empty` cells are represented in the generated enums
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum EmptyHandling {
+    /// Empty cells become the `Null` variant
+    #[default]
+    Merge,
+    /// Every enum gets an `Empty` variant besides `Null`
+    Distinct,
+}
+
+impl GenOptions {
+    /// The variants representing a missing value, to be appended to every enum
+    pub fn missing_variants(&self) -> &'static str {
+        match self.empty_handling {
+            EmptyHandling::Merge => "Null",
+            EmptyHandling::Distinct => "Null, Empty",
+        }
+    }
+
+    /// The type of a single value of the column, `T` in `Vec<T>`
+    pub fn element_type(&self, column: &SchemaColumn) -> String {
+        let name = &column.ident;
+        if !self.option_columns {
+            return name.clone();
+        }
+        let element_type = match column.kind {
+            ColumnKind::Int(int_type) => int_type.to_string(),
+            ColumnKind::Float => "f64".to_string(),
+            ColumnKind::Text => "String".to_string(),
+            ColumnKind::Semantic(pattern) => pattern.rust_type().to_string(),
+            ColumnKind::Categorical | ColumnKind::Mixed => name.clone(),
+        };
+        match column.nullable {
+            true => format!("Option<{element_type}>"),
+            false => element_type,
+        }
+    }
+
+    /// Whether the values of the column can fall into `Other(String)`,
+    /// such columns are not `Copy`
+    pub fn has_other_variant(&self, column: &SchemaColumn) -> bool {
+        self.other_variant && matches!(column.kind, ColumnKind::Categorical | ColumnKind::Mixed)
+    }
+
+    /// Whether the values of the column are `Copy`, the others are cloned
+    pub fn is_copy(&self, column: &SchemaColumn) -> bool {
+        match column.kind {
+            ColumnKind::Text => false,
+            ColumnKind::Semantic(pattern) => pattern.is_copy(),
+            _ => !self.has_other_variant(column),
+        }
+    }
+
+    /// The serde derive to put on a generated type, empty without `serde`
+    pub fn serde_derive(&self) -> &'static str {
+        match self.serde {
+            true => "#[derive(csv_deserializer::serde::Serialize, csv_deserializer::serde::Deserialize)]\n#[serde(crate = \"csv_deserializer::serde\")]\n",
+            false => "",
+        }
+    }
+
+    /// Wrap the generated code in the module, if any
+    pub fn wrap_in_module(&self, code: String) -> String {
+        match &self.module {
+            Some(module) => format!("{} mod {module} {{\n{code}\n}}\n", self.visibility),
+            None => code,
+        }
+    }
+
+    /// The variant used for empty cells
+    pub fn empty_variant(&self) -> &'static str {
+        match self.empty_handling {
+            EmptyHandling::Merge => "Null",
+            EmptyHandling::Distinct => "Empty",
+        }
+    }
+}
@@ -1,14 +1,35 @@
+pub mod code_gen;
 pub mod csv_dataset;
 pub mod csv_types;
 pub mod dataset_info;
 pub mod enum_gen;
+pub mod gen_options;
+pub mod number_format;
+pub mod overrides;
+pub mod patterns;
+pub mod profile;
 pub mod sanitizer;
+pub mod schema;
+pub mod schema_diff;
+pub mod sketch;
+pub mod stats;
 pub mod struct_gen;
+pub mod validate;
 
-use crate::{csv_types::CsvAny, sanitizer::sanitize_identifier};
+use crate::{
+    csv_types::CsvAny,
+    number_format::{NumberFormat, NumericMarker},
+    overrides::ColumnType,
+    sanitizer::sanitize_identifier,
+};
 
 pub const COLUMN_TYPE_ENUM_NAME: &str = "CsvColumn";
 pub const MAIN_STRUCT_NAME: &str = "CsvDataFrame";
+pub const ROW_STRUCT_NAME: &str = "CsvRow";
+pub const ERROR_ENUM_NAME: &str = "CsvDataFrameError";
+
+/// Re-exported for the generated code, which derives `Deserialize` with it
+pub use serde;
 
 /// A view over all the column names and
 /// all the values
@@ -33,16 +54,19 @@ pub struct RemovedColumn{
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ColName {
     pub raw: String,
     pub sanitized: SanitizedStr,
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SanitizedStr(pub String);
 
 #[derive(Debug, Default)]
-pub struct NullValues<'a>(pub Vec<&'a str>);
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NullValues<'a>(#[cfg_attr(feature = "serde", serde(borrow))] pub Vec<&'a str>);
 
 impl ColName {
     pub fn new(raw: &str) -> Self {
@@ -58,28 +82,80 @@ impl ColName {
 struct RawCsvValue<'reader>(&'reader str);
 
 impl<'reader> RawCsvValue<'reader> {
-    fn as_csvany(&self, null_values: &NullValues) -> CsvAny {
+    /// Convert the raw cell without trying to parse it as a number
+    fn as_text(&self, null_values: &NullValues) -> CsvAny {
+        if self.0.is_empty() {
+            CsvAny::Empty
+        } else if null_values.0.contains(&self.0) {
+            CsvAny::Null
+        } else {
+            CsvAny::Str(self.0.to_owned())
+        }
+    }
+
+    /// Convert the raw cell to the type forced on its column,
+    /// `None` if the value cannot be represented by it
+    fn as_type(
+        &self,
+        null_values: &NullValues,
+        number_format: &NumberFormat,
+        column_type: ColumnType,
+    ) -> Option<(CsvAny, Option<NumericMarker>)> {
+        if let ColumnType::Categorical | ColumnType::Text = column_type {
+            return Some((self.as_text(null_values), None));
+        }
+        let (csvany, marker) = self.as_csvany(null_values, number_format);
+        let csvany = match (column_type, csvany) {
+            (_, any @ (CsvAny::Null | CsvAny::Empty)) => any,
+            (ColumnType::Float, CsvAny::Int(i)) => CsvAny::Float(i as f64),
+            (ColumnType::Float, CsvAny::BigInt(i)) => CsvAny::Float(i as f64),
+            (ColumnType::Float, any @ CsvAny::Float(_)) => any,
+            (ColumnType::Int(ty), any @ CsvAny::Int(i)) if ColumnType::int_fits(ty, i.into()) => any,
+            (ColumnType::Int(ty), any @ CsvAny::BigInt(i)) if ColumnType::int_fits(ty, i) => any,
+            _ => return None,
+        };
+        Some((csvany, marker))
+    }
+
+    /// Codes like `00123` where the leading zeros are meaningful
+    fn is_leading_zero_code(&self) -> bool {
+        self.0.len() > 1 && self.0.starts_with('0') && self.0.bytes().all(|b| b.is_ascii_digit())
+    }
+
+    /// Convert the raw cell, the marker stripped from numeric
+    /// values (`%`, `$`, ..) is returned alongside
+    fn as_csvany(
+        &self,
+        null_values: &NullValues,
+        number_format: &NumberFormat,
+    ) -> (CsvAny, Option<NumericMarker>) {
         if self.0.is_empty() {
-            return CsvAny::Empty;
+            return (CsvAny::Empty, None);
         } else if null_values.0.contains(&self.0) {
             /*  */
-            return CsvAny::Null;
+            return (CsvAny::Null, None);
         }
 
-        let try_float = self.0.parse::<f64>();
-        let try_int = self.0.parse::<i64>();
+        let Some((normalized, marker)) = number_format.normalize(self.0) else {
+            return (CsvAny::Str(self.0.to_owned()), None);
+        };
+        let try_float = normalized.parse::<f64>();
+        let try_int = normalized.parse::<i128>();
 
         match (try_float, try_int) {
-            (_, Ok(int)) => CsvAny::Int(int),
-            (Ok(float), Err(_)) => CsvAny::Float(float),
-            (Err(_), Err(_)) => CsvAny::Str(self.0.to_owned()),
+            (_, Ok(int)) => (CsvAny::from_integer(int), marker),
+            (Ok(float), Err(_)) => (CsvAny::Float(float), marker),
+            (Err(_), Err(_)) => (CsvAny::Str(self.0.to_owned()), None),
         }
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::csv_dataset::CsvDataset;
+    use crate::{
+        csv_dataset::{CsvDataset, ParseOptions},
+        dataset_info::ColumnInfo,
+    };
 
     use super::*;
 
@@ -122,7 +198,7 @@ mod test {
             names,
             values,
             null_values: NullValues(Vec::new()),
-            info: Vec::new(),
+            ..Default::default()
         };
 
         CsvDataset::populate_column_infos(&mut df);
@@ -150,4 +226,264 @@ mod test {
         // Verify sanitized name on the Info struct matches input
         assert_eq!(info1.column_name.raw, "mixed_data");
     }
+
+    #[test]
+    fn narrowest_int_type() {
+        let mut df = CsvDataset::default();
+        df.push("small", vec![0.into(), 200.into(), CsvAny::Null]);
+        df.push("signed", vec![(-5).into(), 40_000.into()]);
+        df.push(
+            "id",
+            vec![
+                RawCsvValue("18446744073709551615").as_csvany(&NullValues::default(), &NumberFormat::default()).0,
+                1.into(),
+            ],
+        );
+        CsvDataset::populate_column_infos(&mut df);
+
+        let types = df.info.iter().map(ColumnInfo::int_type).collect::<Vec<_>>();
+        assert_eq!(types, ["u8", "i32", "u64"]);
+        assert_eq!(df.values[2][0], CsvAny::BigInt(u64::MAX as i128));
+    }
+
+    #[test]
+    fn leading_zero_codes_stay_text() {
+        let csv = "zip,age,sku\n+7,30,7\n4.50,41,8\n00123,52,9\n\"1,234\",63,10\n";
+        let rdr = csv::Reader::from_reader(csv.as_bytes());
+        let options = ParseOptions {
+            text_columns: vec!["sku".to_string()],
+            ..Default::default()
+        };
+        let df = CsvDataset::with_options(rdr, NullValues::default(), options);
+
+        assert_eq!(df.values[0], vec!["+7".into(), "4.50".into(), "00123".into(), "1,234".into()]);
+        assert!(df.markers[0].is_empty());
+        assert_eq!(df.values[1], vec![30.into(), 41.into(), 52.into(), 63.into()]);
+        assert_eq!(df.values[2], vec!["7".into(), "8".into(), "9".into(), "10".into()]);
+    }
+
+    #[test]
+    fn overrides_report_violations() {
+        let csv = "age,status,notes\n30,1,ok\n300,2,\nNA,1,fine\n";
+        let rdr = csv::Reader::from_reader(csv.as_bytes());
+        let options = ParseOptions {
+            overrides: toml::from_str(
+                "[columns]\nage = \"u8\"\nstatus = \"categorical\"\nnotes = \"text\"\nnote = \"text\"",
+            )
+            .unwrap(),
+            ..Default::default()
+        };
+        let df = CsvDataset::with_options(rdr, NullValues(vec!["NA"]), options);
+
+        assert_eq!(df.values[0], vec![30.into(), CsvAny::Null, CsvAny::Null]);
+        assert_eq!(df.values[1], vec!["1".into(), "2".into(), "1".into()]);
+        assert_eq!(df.violations.len(), 2);
+        assert_eq!(df.violations[0].column, "note");
+        assert_eq!(df.violations[0].row, None);
+        assert_eq!(df.violations[1].row, Some(1));
+        assert_eq!(df.violations[1].raw, "300");
+        assert_eq!(df.column_info(0).int_type(), "u8");
+    }
+
+    #[test]
+    fn ints_and_floats_make_a_float_column() {
+        use crate::{dataset_info::ColumnKind, gen_options::GenOptions, schema::Schema};
+
+        let rdr = csv::Reader::from_reader("score,big\n1,1e400\n2.5,2\nNA,3\n".as_bytes());
+        let schema = Schema::from_dataset(&CsvDataset::new(rdr, NullValues(vec!["NA"])));
+        assert_eq!(schema.columns[0].kind, ColumnKind::Float);
+        assert_eq!(schema.columns[1].kind, ColumnKind::Float);
+
+        let options = GenOptions::default();
+        assert_eq!(options.element_type(&schema.columns[0]), "score");
+        let options = GenOptions {
+            option_columns: true,
+            ..Default::default()
+        };
+        assert_eq!(options.element_type(&schema.columns[0]), "Option<f64>");
+        assert_eq!(options.element_type(&schema.columns[1]), "f64");
+        let code = code_gen::gen_rust_code(&schema, &options);
+        assert!(code.contains("pub score: Vec<Option<f64>>"));
+    }
+
+    #[test]
+    fn patterns_are_only_detected_with_semantic_types() {
+        use crate::patterns::Pattern;
+
+        let csv = "mail\na@b.com\nc@d.org\n";
+        let df = CsvDataset::new(csv::Reader::from_reader(csv.as_bytes()), NullValues::default());
+        assert!(df.column_info(0).patterns.is_empty());
+
+        let options = ParseOptions {
+            semantic_types: true,
+            ..Default::default()
+        };
+        let df = CsvDataset::with_options(csv::Reader::from_reader(csv.as_bytes()), NullValues::default(), options);
+        let info = df.column_info(0);
+        assert_eq!(info.patterns[0].pattern, Pattern::Email);
+        assert_eq!(info.semantic_type, Some(Pattern::Email));
+    }
+
+    #[test]
+    fn nan_cells_do_not_break_the_unique_values() {
+        let rdr = csv::Reader::from_reader("score\nNaN\n1.5\nNaN\ninf\n".as_bytes());
+        let dataset = CsvDataset::new(rdr, NullValues(vec![]));
+        let info = dataset.column_info(0);
+        assert_eq!(info.unique_values[0].csvany, CsvAny::Float(1.5));
+        assert_eq!(info.unique_values[1].csvany, CsvAny::Float(f64::INFINITY));
+        assert!(matches!(info.unique_values[2].csvany, CsvAny::Float(x) if x.is_nan()));
+    }
+
+    #[test]
+    fn rows_read_overridden_columns_like_the_dataset() {
+        use crate::{dataset_info::ColumnKind, gen_options::GenOptions, schema::Schema};
+
+        let rdr = csv::Reader::from_reader("score,code\n10,1\n20,2\n".as_bytes());
+        let mut options = ParseOptions::default();
+        options.overrides.insert("score", ColumnType::Float);
+        options.overrides.insert("code", ColumnType::Categorical);
+        let df = CsvDataset::with_options(rdr, NullValues(vec!["NA"]), options);
+        let schema = Schema::from_dataset(&df);
+        assert_eq!(schema.columns[0].kind, ColumnKind::Float);
+
+        let read = ["10", "1"].iter().zip(&schema.columns).map(|(raw, column)| {
+            CsvAny::parse_as(raw, column.kind, &NullValues(vec!["NA"]), &schema.number_format)
+        });
+        assert_eq!(read.collect::<Vec<_>>(), [df.values[0][0].clone(), df.values[1][0].clone()]);
+        let code = code_gen::gen_rust_code(&schema, &GenOptions::default());
+        assert!(code.contains("CsvAny::parse_as(&raw.score, csv_deserializer::dataset_info::ColumnKind::Float,"));
+    }
+
+    #[test]
+    fn variants_count_their_cells() {
+        let rdr = csv::Reader::from_reader("species\nvirginica\nsetosa\nNA\nsetosa\n".as_bytes());
+        let df = CsvDataset::new(rdr, NullValues(vec!["NA"]));
+
+        let setosa = &df.column_info(0).unique_values[0];
+        assert_eq!((setosa.raw.as_str(), setosa.count, setosa.first_row), ("setosa", 2, Some(1)));
+        assert_eq!(
+            df.value_counts(0),
+            [("setosa".into(), 2), ("virginica".into(), 1), (CsvAny::Null, 1)]
+        );
+    }
+
+    #[test]
+    fn keys_get_lookup_methods() {
+        let csv = "order_id,store,day,price\n1001,A,1,2.5\n1002,A,2,2.5\n1003,B,1,3.5\n1004,B,2,3.5\n";
+        let rdr = csv::Reader::from_reader(csv.as_bytes());
+        let schema = schema::Schema::from_dataset(&CsvDataset::new(rdr, NullValues(vec!["NA"])));
+        assert!(schema.composite_keys.is_empty());
+
+        let options = ParseOptions {
+            composite_keys: true,
+            ..Default::default()
+        };
+        let rdr = csv::Reader::from_reader(csv.as_bytes());
+        let schema = schema::Schema::from_dataset(&CsvDataset::with_options(rdr, NullValues(vec!["NA"]), options));
+
+        let keys = schema.columns.iter().filter(|column| column.key).map(|column| column.name.as_str());
+        assert_eq!(keys.collect::<Vec<_>>(), ["order_id"]);
+        assert_eq!(schema.composite_keys, [["store".to_string(), "day".to_string()]]);
+        let code = code_gen::gen_rust_code(&schema, &gen_options::GenOptions::default());
+        assert!(code.contains("pub fn get_by_order_id(&self, key: &order_id) -> Option<CsvRow>"));
+        assert!(code.contains("pub fn index_by_store_and_day(&self)"));
+        assert!(code.contains("rows.entry(self.order_id[row]).or_insert(row);"));
+    }
+
+    #[test]
+    fn id_columns_past_the_distinct_limit() {
+        let csv = (0..500).fold("id,group\n".to_string(), |csv, i| csv + &format!("user{i},g{}\n", i % 3));
+        let rdr = csv::Reader::from_reader(csv.as_bytes());
+        let options = ParseOptions {
+            distinct_limit: Some(50),
+            ..Default::default()
+        };
+        let df = CsvDataset::with_options(rdr, NullValues(vec!["NA"]), options);
+
+        let id = df.column_info(0);
+        assert_eq!(id.kind(), dataset_info::ColumnKind::Text);
+        assert!(matches!(id.distinct, sketch::Cardinality::Approximate(n) if (480..520).contains(&n)));
+        assert_eq!(id.unique_values.len(), 1); // only `Null`
+        let group = df.column_info(1);
+        assert_eq!(group.distinct, sketch::Cardinality::Exact(3));
+        assert_eq!(group.top_values[0].value, "g0");
+    }
+
+    #[test]
+    fn csvany_display_is_the_raw_value() {
+        // used by the generated errors to show the offending cell
+        let values = [
+            CsvAny::from("setosa"),
+            CsvAny::from(-3),
+            CsvAny::from(2.5),
+            CsvAny::Empty,
+        ];
+        let shown = values.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(shown, ["setosa", "-3", "2.5", ""]);
+    }
+
+    #[test]
+    fn type_names_from_file_stem() {
+        use crate::gen_options::TypeNames;
+
+        let names = TypeNames::with_prefix("sales_2024-q1");
+        assert_eq!(names.frame, "Sales2024Q1Frame");
+        assert_eq!(names.column, "Sales2024Q1Column");
+        assert_eq!(names.row, "Sales2024Q1Row");
+        assert_eq!(names.error, "Sales2024Q1FrameError");
+        assert_eq!(TypeNames::with_prefix("2024").frame, "Csv2024Frame");
+    }
+
+    #[test]
+    fn schema_is_editable() {
+        use crate::{enum_gen::generate_enums_from, gen_options::GenOptions, schema::Schema};
+
+        let data = "species,petals\nIris-setosa,3\nNA,4\n";
+        let rdr = csv::ReaderBuilder::new().from_reader(data.as_bytes());
+        let df = CsvDataset::new(rdr, NullValues(vec!["NA"]));
+        let schema = Schema::from_dataset(&df);
+        assert!(schema.columns[0].nullable);
+        assert_eq!(schema.columns[1].kind.to_string(), "u8");
+
+        let toml = toml::to_string(&schema).unwrap();
+        let mut back: Schema = toml::from_str(&toml).unwrap();
+        assert_eq!(back, schema);
+        back.columns[0].categories[0].ident = "Setosa".to_string();
+        let code = generate_enums_from(&back, &GenOptions::default());
+        assert!(code.contains("\"Iris-setosa\" => Setosa,"));
+    }
+
+    #[test]
+    fn code_from_a_saved_schema() {
+        use crate::{code_gen, gen_options::GenOptions, schema::Schema};
+
+        let data = "species,petals\nsetosa,3\nNA,4.5\n";
+        let rdr = csv::ReaderBuilder::new().from_reader(data.as_bytes());
+        let schema = Schema::from_dataset(&CsvDataset::new(rdr, NullValues(vec!["NA"])));
+        // unique, concurrent runs of the tests don't share the file
+        let path = std::env::temp_dir().join(format!("csv_deserializer_saved_schema_{}.json", std::process::id()));
+        schema.save(&path).unwrap();
+
+        let options = GenOptions::default();
+        let code = code_gen::gen_rust_code_from_file(&path, &options);
+        std::fs::remove_file(&path).unwrap();
+        let code = code.unwrap();
+        assert_eq!(code, code_gen::gen_rust_code(&schema, &options));
+        assert!(code.contains("NullValues(vec![\"NA\", ])"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn dataset_serde_round_trip() {
+        let data = "species,petals\nsetosa,3\nNA,4\n";
+        let rdr = csv::ReaderBuilder::new().from_reader(data.as_bytes());
+        let mut df = CsvDataset::new(rdr, NullValues(vec!["NA"]));
+        CsvDataset::populate_column_infos(&mut df);
+
+        let json = serde_json::to_string(&df).unwrap();
+        let back: CsvDataset = serde_json::from_str(&json).unwrap();
+        assert_eq!(back.values, df.values);
+        assert_eq!(back.null_values.0, ["NA"]);
+        assert_eq!(serde_json::to_string(&back.info).unwrap(), serde_json::to_string(&df.info).unwrap());
+    }
 }
@@ -2,54 +2,445 @@
 // use csv_types::*;
 
 
-use clap::Parser;
+use clap::{Args, Parser, Subcommand, ValueEnum};
 
-use std::{error::Error, fmt, fs::File, path::PathBuf};
+use std::{error::Error, fmt, fs::File, path::{Path, PathBuf}, process::ExitCode};
 
 use csv_deserializer::{
-    csv_dataset::CsvDataset, NullValues, enum_gen::generate_enums_from, struct_gen::gen_struct,
+    code_gen::{GENERATED_FILE_HEADER, gen_files_rust_code, gen_rust_code, module_name},
+    csv_dataset::{CsvDataset, ParseOptions}, NullValues,
+    gen_options::{EmptyHandling, GenOptions, TypeNames, Visibility},
+    number_format::NumberFormat, overrides::{ColumnType, TypeOverrides}, profile::{markdown_table, profile_dataset}, schema::Schema, schema_diff::diff_schemas, sketch::HEAVY_HITTERS_CAPACITY,
+    validate::{ValidationReport, validate_csv},
 };
 
-/// Print to stdout the code generation for the provided `CsvDataset`
-fn print_csv_rust_code(dataset: &mut CsvDataset) {
-    let enums = generate_enums_from(dataset);
-    let struc = gen_struct(dataset);
-    let import = gen_imports();
-    println!("#![allow(unused,non_snake_case,non_camel_case_types)]{import}\n{enums}\n{struc}");
-}
-fn gen_imports()-> String{
-    String::from("\
-use csv_deserializer::{create_enum, csv_dataset::CsvDataset, csv_types::CsvAny};
-use std::str::FromStr;
-\n")
-}
-
 #[derive(Parser)]
-#[command(author = "AliothCancer", version)]
+#[command(author = "AliothCancer", version, subcommand_negates_reqs = true, args_conflicts_with_subcommands = true)]
 #[derive(Debug)]
 struct Cli {
-    #[arg(short = 'i', long = "input-file", value_name = "input_file", value_parser=custom_csv_path_validator)]
-    input_file: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// One or more csv files, columns with the same name and categories in common share their enum
+    #[arg(short = 'i', long = "input-file", value_name = "input_file", value_parser=custom_csv_path_validator, required = true, num_args = 1..)]
+    input_files: Vec<PathBuf>,
+    #[command(flatten)]
+    parse_args: ParseArgs,
+    #[command(flatten)]
+    gen_args: GenArgs,
+    /// Save the inferred schema to a .toml or .json file, it can be edited by hand
+    #[arg(long = "save-schema", value_name = "schema_file")]
+    save_schema: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Generate the code from a schema saved with `--save-schema`, the csv is not needed
+    FromSchema {
+        #[arg(value_name = "schema_file")]
+        schema_file: PathBuf,
+        #[command(flatten)]
+        gen_args: GenArgs,
+    },
+    /// Compare two csv files, or a saved schema and a csv, and tell which changes break the generated code
+    Diff {
+        /// A csv file or a schema saved with `--save-schema`
+        #[arg(value_name = "old")]
+        old: PathBuf,
+        /// A csv file or a schema saved with `--save-schema`
+        #[arg(value_name = "new")]
+        new: PathBuf,
+        #[command(flatten)]
+        parse_args: ParseArgs,
+        /// The code is generated with `--option-columns`, nullability changes its types
+        #[arg(long = "option-columns")]
+        option_columns: bool,
+        /// The code is generated with `--other-variant`, new categories are fine
+        #[arg(long = "other-variant")]
+        other_variant: bool,
+        #[arg(long = "format", value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Check a csv against a saved schema and list every cell which wouldn't convert,
+    /// the exit status is 1 if there is any
+    Validate {
+        #[arg(value_name = "input_file", value_parser = custom_csv_path_validator)]
+        input_file: PathBuf,
+        #[arg(value_name = "schema_file")]
+        schema_file: PathBuf,
+        /// The code is generated with `--other-variant`, unknown categories are fine
+        #[arg(long = "other-variant")]
+        other_variant: bool,
+        /// The code is generated with `--option-columns`, missing values only fit nullable columns
+        #[arg(long = "option-columns")]
+        option_columns: bool,
+        #[arg(long = "format", value_enum, default_value_t)]
+        format: OutputFormat,
+    },
+    /// Print the type counts, nulls, distinct values and most frequent values of each column
+    Profile {
+        #[arg(value_name = "input_file", value_parser = custom_csv_path_validator)]
+        input_file: PathBuf,
+        #[command(flatten)]
+        parse_args: ParseArgs,
+        /// How many of the most frequent values to show, at most 100
+        #[arg(long = "top", value_name = "n", default_value_t = 5, value_parser = parse_top)]
+        top: usize,
+        #[arg(long = "format", value_enum, default_value_t)]
+        format: ProfileFormat,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+enum ProfileFormat {
+    #[default]
+    Human,
+    Markdown,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default)]
+enum OutputFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+// how the cells of the csv are read
+#[derive(Args, Debug)]
+struct ParseArgs {
     #[arg(short = 'n', long = "null-values", value_name = "a,b,..")]
-    null_values: Option<String>
-}
-
-fn main() -> Result<(), Box<dyn Error>> {
-    let Cli { input_file, null_values } = Cli::parse();
-    let file = File::open(input_file)?;
-    let rdr = csv::ReaderBuilder::new()
-        .has_headers(true)
-        .from_reader(file);
-    let possible_nulls = match &null_values{
-        Some(s) => s.split(',').map(str::trim).collect::<Vec<&str>>(),
-        None => Vec::new(),
-    };
+    null_values: Option<String>,
+    #[arg(long = "decimal-separator", value_name = "char", default_value_t = '.')]
+    decimal_separator: char,
+    /// Thousands separator, e.g. `,` for `1,234.56`
+    #[arg(long = "grouping-separator", value_name = "char")]
+    grouping_separator: Option<char>,
+    /// Parse `12%` as a number
+    #[arg(long = "percent")]
+    percent: bool,
+    /// Currency symbols to strip from numbers, e.g. `$,€`
+    #[arg(long = "currency", value_name = "a,b,..")]
+    currency: Option<String>,
+    /// Parse codes like `00123` as numbers instead of keeping the column as text
+    #[arg(long = "numeric-leading-zeros")]
+    numeric_leading_zeros: bool,
+    /// Columns to always keep as text, e.g. `zip,sku`
+    #[arg(long = "text-columns", value_name = "a,b,..")]
+    text_columns: Option<String>,
+    /// A .toml or .json file forcing the type of some columns
+    #[arg(long = "overrides", value_name = "overrides_file")]
+    overrides: Option<PathBuf>,
+    /// Force the type of a column: categorical, text, float, int, u8, i32, ..
+    #[arg(long = "column-type", value_name = "name=type", value_parser = parse_column_type)]
+    column_types: Vec<(String, ColumnType)>,
+    /// Keep at most n unique values per column, then estimate the distinct count.
+    /// String columns with more become text instead of enums
+    #[arg(long = "distinct-limit", value_name = "n")]
+    distinct_limit: Option<usize>,
+    /// Use validated types (Email, Uuid, Ipv4Addr, ..) for the string columns
+    /// where every value is an email, url, uuid, ip, hex hash, phone number or json object.
+    /// The patterns are only detected, and reported by profile, with this flag
+    #[arg(long = "semantic-types")]
+    semantic_types: bool,
+    /// Look for pairs of columns unique together and generate lookups by them, slow on wide csvs
+    #[arg(long = "composite-keys")]
+    composite_keys: bool,
+}
+
+// how the code is generated, shared by the subcommands
+#[derive(Args, Debug)]
+struct GenArgs {
+    /// Keep `Empty` and `Null` as distinct variants in every generated enum
+    #[arg(long = "distinct-empty")]
+    distinct_empty: bool,
+    /// Generate `Vec<Option<T>>` columns instead of enums with a `Null` variant
+    #[arg(long = "option-columns")]
+    option_columns: bool,
+    /// Add an `Other(String)` variant to the categorical enums for values not seen in the csv
+    #[arg(long = "other-variant")]
+    other_variant: bool,
+    /// Mark the categorical enums `#[non_exhaustive]`
+    #[arg(long = "non-exhaustive")]
+    non_exhaustive: bool,
+    /// Name of the main struct, e.g. `IrisFrame` for `iris.csv`.
+    /// The row and error types are named after it
+    #[arg(long = "frame-name", value_name = "name")]
+    frame_name: Option<String>,
+    /// Name of the column view enum, e.g. `IrisColumn` for `iris.csv`
+    #[arg(long = "column-enum-name", value_name = "name")]
+    column_enum_name: Option<String>,
+    /// Wrap the code in `mod <name> { .. }`, named after the file when no name is given
+    #[arg(long = "module", value_name = "name")]
+    module: Option<Option<String>>,
+    /// Visibility of the generated types: pub, pub(crate)
+    #[arg(long = "visibility", value_name = "vis", default_value = "pub")]
+    visibility: Visibility,
+    /// Document min, max, mean, quantiles, .. of the numeric columns on their enum,
+    /// or on the frame field with --option-columns
+    #[arg(long = "stats-docs")]
+    stats_docs: bool,
+    /// Derive serde `Serialize` and `Deserialize` on the generated types
+    #[arg(long = "serde")]
+    serde: bool,
+}
+
+impl ParseArgs {
+    fn null_values(&self) -> NullValues<'_> {
+        match &self.null_values {
+            Some(s) => NullValues(s.split(',').map(str::trim).collect()),
+            None => NullValues::default(),
+        }
+    }
+
+    fn parse_options(&self) -> Result<ParseOptions, Box<dyn Error>> {
+        let number_format = NumberFormat {
+            decimal_separator: self.decimal_separator,
+            grouping_separator: self.grouping_separator,
+            percent: self.percent,
+            currency_symbols: match &self.currency {
+                Some(s) => s.split(',').map(|x| x.trim().to_string()).collect(),
+                None => Vec::new(),
+            },
+        };
+
+        let mut overrides = match &self.overrides {
+            Some(path) => TypeOverrides::from_file(path)?,
+            None => TypeOverrides::default(),
+        };
+        self.column_types
+            .iter()
+            .for_each(|(name, column_type)| overrides.insert(name, *column_type));
+
+        Ok(ParseOptions {
+            number_format,
+            preserve_leading_zeros: !self.numeric_leading_zeros,
+            text_columns: match &self.text_columns {
+                Some(s) => s.split(',').map(|x| x.trim().to_string()).collect(),
+                None => Vec::new(),
+            },
+            overrides,
+            distinct_limit: self.distinct_limit,
+            semantic_types: self.semantic_types,
+            composite_keys: self.composite_keys,
+        })
+    }
+
+    /// Infer the schema of a csv, or load it if `path` is a .toml or .json file
+    fn load_schema(&self, path: &Path) -> Result<Schema, Box<dyn Error>> {
+        match path.extension().is_some_and(|ext| ext == "csv") {
+            true => Ok(Schema::from_dataset(&self.read_dataset(path)?)),
+            false => Ok(Schema::from_file(path)?),
+        }
+    }
+
+    /// Read the csv, the override violations are reported on stderr
+    fn read_dataset(&self, input_file: &Path) -> Result<CsvDataset<'_>, Box<dyn Error>> {
+        let rdr = csv::ReaderBuilder::new()
+            .has_headers(true)
+            .from_reader(File::open(input_file)?);
+        let dataset = CsvDataset::with_options(rdr, self.null_values(), self.parse_options()?);
+        // stdout is reserved to the generated code
+        dataset
+            .violations
+            .iter()
+            .for_each(|violation| eprintln!("override violation: {violation}"));
+        Ok(dataset)
+    }
+}
+
+impl GenArgs {
+    /// The types and the module are named after `file_stem`
+    fn gen_options(&self, file_stem: &str) -> GenOptions {
+        GenOptions {
+            empty_handling: match self.distinct_empty {
+                true => EmptyHandling::Distinct,
+                false => EmptyHandling::Merge,
+            },
+            option_columns: self.option_columns,
+            other_variant: self.other_variant,
+            non_exhaustive: self.non_exhaustive,
+            serde: self.serde,
+            names: type_names(file_stem, self.frame_name.clone(), self.column_enum_name.clone()),
+            module: self.module.clone().map(|name| name.unwrap_or_else(|| module_name(file_stem))),
+            visibility: self.visibility,
+            stats_docs: self.stats_docs,
+            shared_enums: Vec::new(),
+        }
+    }
+}
 
-    let mut dataset = CsvDataset::new(rdr, NullValues(possible_nulls));
-    print_csv_rust_code(&mut dataset);
+/// Named after the file, `--frame-name` also renames the row and error types
+fn type_names(file_stem: &str, frame_name: Option<String>, column_enum_name: Option<String>) -> TypeNames {
+    let mut names = TypeNames::with_prefix(file_stem);
+    if let Some(frame) = frame_name {
+        let prefix = frame.strip_suffix("Frame").unwrap_or(&frame);
+        names.row = format!("{prefix}Row");
+        names.error = format!("{frame}Error");
+        names.frame = frame;
+    }
+    if let Some(column) = column_enum_name {
+        names.column = column;
+    }
+    names
+}
+
+fn parse_column_type(s: &str) -> Result<(String, ColumnType), String> {
+    let (name, column_type) = s
+        .split_once('=')
+        .ok_or_else(|| format!("Expected `name=type`, found '{s}'"))?;
+    Ok((name.trim().to_string(), column_type.parse()?))
+}
+
+/// Only the `HEAVY_HITTERS_CAPACITY` most frequent values are tracked
+fn parse_top(s: &str) -> Result<usize, String> {
+    let top = s.parse::<usize>().map_err(|e| e.to_string())?;
+    match top <= HEAVY_HITTERS_CAPACITY {
+        true => Ok(top),
+        false => Err(format!("at most {HEAVY_HITTERS_CAPACITY} values are tracked")),
+    }
+}
+
+/// Print the changes from `old` to `new`, each one marked as breaking or not
+fn print_diff(old: &Schema, new: &Schema, options: &GenOptions, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let changes = diff_schemas(old, new);
+    let breaking = changes.iter().filter(|change| change.is_breaking(options)).count();
+    match format {
+        OutputFormat::Human => {
+            changes.iter().for_each(|change| match change.is_breaking(options) {
+                true => println!("breaking      {change}"),
+                false => println!("non-breaking  {change}"),
+            });
+            match changes.len() {
+                0 => println!("no changes"),
+                n => println!("{breaking} breaking and {} non-breaking changes", n - breaking),
+            }
+        }
+        OutputFormat::Json => {
+            let changes = changes
+                .iter()
+                .map(|change| {
+                    let mut value = serde_json::to_value(change)?;
+                    value["breaking"] = change.is_breaking(options).into();
+                    Ok(value)
+                })
+                .collect::<Result<Vec<_>, serde_json::Error>>()?;
+            let report = serde_json::json!({
+                "breaking": breaking,
+                "non_breaking": changes.len() - breaking,
+                "changes": changes,
+            });
+            println!("{}", serde_json::to_string_pretty(&report)?);
+        }
+    }
+    Ok(())
+}
+
+fn print_validation(report: &ValidationReport, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    match format {
+        OutputFormat::Human => {
+            report
+                .missing_columns
+                .iter()
+                .for_each(|column| println!("missing column `{column}`"));
+            report.invalid_cells.iter().for_each(|cell| println!("{cell}"));
+            for (column, counts) in report.counts() {
+                let counts = counts
+                    .iter()
+                    .map(|(problem, count)| format!("{count} {problem}"))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                println!("`{column}`: {counts}");
+            }
+            println!(
+                "{} rows, {} invalid cells, {} missing columns",
+                report.rows,
+                report.invalid_cells.len(),
+                report.missing_columns.len()
+            );
+        }
+        OutputFormat::Json => {
+            let mut value = serde_json::to_value(report)?;
+            value["counts"] = serde_json::to_value(report.counts())?;
+            println!("{}", serde_json::to_string_pretty(&value)?);
+        }
+    }
     Ok(())
 }
 
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    let Cli {
+        command,
+        input_files,
+        parse_args,
+        gen_args,
+        save_schema,
+    } = Cli::parse();
+    match command {
+        Some(Command::FromSchema { schema_file, gen_args }) => {
+            let schema = Schema::from_file(&schema_file)?;
+            let code = gen_rust_code(&schema, &gen_args.gen_options(&file_stem(&schema_file)));
+            println!("{GENERATED_FILE_HEADER}{code}");
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Diff { old, new, parse_args, option_columns, other_variant, format }) => {
+            let options = GenOptions { option_columns, other_variant, ..Default::default() };
+            print_diff(&parse_args.load_schema(&old)?, &parse_args.load_schema(&new)?, &options, format)?;
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Profile { input_file, parse_args, top, format }) => {
+            let profiles = profile_dataset(&parse_args.read_dataset(&input_file)?, top);
+            match format {
+                ProfileFormat::Human => profiles.iter().for_each(|profile| println!("{profile}\n")),
+                ProfileFormat::Markdown => print!("{}", markdown_table(&profiles)),
+                ProfileFormat::Json => println!("{}", serde_json::to_string_pretty(&profiles)?),
+            }
+            return Ok(ExitCode::SUCCESS);
+        }
+        Some(Command::Validate { input_file, schema_file, other_variant, option_columns, format }) => {
+            let schema = Schema::from_file(&schema_file)?;
+            let options = GenOptions { other_variant, option_columns, ..Default::default() };
+            let report = validate_csv(csv::Reader::from_path(&input_file)?, &schema, &options)?;
+            print_validation(&report, format)?;
+            return Ok(match report.is_valid() {
+                true => ExitCode::SUCCESS,
+                false => ExitCode::FAILURE,
+            });
+        }
+        None => {}
+    }
+
+    let mut schemas = Vec::new();
+    for input_file in &input_files {
+        let dataset = parse_args.read_dataset(input_file)?;
+        schemas.push((file_stem(input_file), Schema::from_dataset(&dataset)));
+    }
+    if schemas.len() > 1
+        && (gen_args.frame_name.is_some() || gen_args.column_enum_name.is_some() || gen_args.module == Some(None))
+    {
+        return Err("With more input files the types and modules are named after each file, \
+            `--frame-name`, `--column-enum-name` and `--module` without a name are not allowed".into());
+    }
+    if let Some(path) = &save_schema {
+        let [(_, schema)] = schemas.as_slice() else {
+            return Err("`--save-schema` works with a single input file".into());
+        };
+        schema.save(path)?;
+    }
+    let gen_options = gen_args.gen_options(&schemas[0].0);
+    let code = match schemas.as_slice() {
+        [(_, schema)] => gen_rust_code(schema, &gen_options),
+        schemas => gen_files_rust_code(schemas, &gen_options),
+    };
+    println!("{GENERATED_FILE_HEADER}{code}");
+    Ok(ExitCode::SUCCESS)
+}
+
 
 #[derive(Debug)]
 pub enum LocalCsvError {
@@ -0,0 +1,187 @@
+use std::{borrow::Cow, fmt::Display};
+
+/// Describes how numbers are written inside the csv file,
+/// the default is the plain rust notation (`1234.56`)
+///
+/// # Examples
+/// ```
+/// use csv_deserializer::number_format::{NumberFormat, NumericMarker};
+///
+/// let format = NumberFormat {
+///     decimal_separator: ',',
+///     grouping_separator: Some('.'),
+///     percent: true,
+///     currency_symbols: vec!["€".to_string()],
+/// };
+/// let (normalized, marker) = format.normalize("1.234,56 €").unwrap();
+///
+/// assert_eq!(normalized, "1234.56");
+/// assert_eq!(marker, Some(NumericMarker::Currency("€".to_string())));
+/// ```
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct NumberFormat {
+    pub decimal_separator: char,
+    /// Thousands separator, groups must be made of 3 digits
+    pub grouping_separator: Option<char>,
+    /// Recognize a trailing `%`
+    pub percent: bool,
+    /// Recognize these symbols before or after the number
+    pub currency_symbols: Vec<String>,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        Self {
+            decimal_separator: '.',
+            grouping_separator: None,
+            percent: false,
+            currency_symbols: Vec::new(),
+        }
+    }
+}
+
+/// A unit or marker stripped from a numeric cell
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum NumericMarker {
+    Percent,
+    Currency(String),
+}
+
+impl Display for NumericMarker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NumericMarker::Percent => write!(f, "%"),
+            NumericMarker::Currency(symbol) => write!(f, "{symbol}"),
+        }
+    }
+}
+
+impl NumberFormat {
+    fn is_plain(&self) -> bool {
+        self.decimal_separator == '.'
+            && self.grouping_separator.is_none()
+            && !self.percent
+            && self.currency_symbols.is_empty()
+    }
+
+    /// Convert `raw` to a string that rust can parse as `i64` or `f64`,
+    /// returns `None` if `raw` is not a number written in this format.
+    ///
+    /// The marker stripped from the value, if any, is returned alongside.
+    pub fn normalize<'s>(&self, raw: &'s str) -> Option<(Cow<'s, str>, Option<NumericMarker>)> {
+        if self.is_plain() {
+            return Some((Cow::Borrowed(raw), None));
+        }
+
+        let mut marker = None;
+        let mut body = raw;
+        if self.percent
+            && let Some(rest) = body.trim_end().strip_suffix('%')
+        {
+            body = rest.trim_end();
+            marker = Some(NumericMarker::Percent);
+        }
+
+        let (mut sign, mut rest) = split_sign(body.trim_start());
+        if marker.is_none() {
+            for symbol in &self.currency_symbols {
+                if let Some(stripped) = rest.strip_prefix(symbol.as_str()) {
+                    rest = stripped.trim_start();
+                } else if let Some(stripped) = rest.trim_end().strip_suffix(symbol.as_str()) {
+                    rest = stripped.trim_end();
+                } else {
+                    continue;
+                }
+                marker = Some(NumericMarker::Currency(symbol.clone()));
+                break;
+            }
+            // the sign can also follow the currency symbol, `$-3.50`
+            if sign.is_empty() {
+                (sign, rest) = split_sign(rest);
+            }
+        }
+
+        let normalized = self.normalize_separators(rest)?;
+        if !normalized.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+            return None;
+        }
+        Some((Cow::Owned(format!("{sign}{normalized}")), marker))
+    }
+
+    /// Remove the grouping separator and replace the decimal one with `.`
+    fn normalize_separators(&self, body: &str) -> Option<String> {
+        let (int_part, frac_part) = match body.split_once(self.decimal_separator) {
+            Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+            None => (body, None),
+        };
+
+        let int_part = match self.grouping_separator {
+            Some(group) if int_part.contains(group) => {
+                let mut groups = int_part.split(group);
+                let first = groups.next()?;
+                let valid = (1..=3).contains(&first.len())
+                    && first.chars().all(|c| c.is_ascii_digit())
+                    && groups.all(|g| g.len() == 3 && g.chars().all(|c| c.is_ascii_digit()));
+                if !valid {
+                    return None;
+                }
+                int_part.replace(group, "")
+            }
+            _ => int_part.to_string(),
+        };
+
+        // a `.` which is not the decimal separator is not expected here
+        if self.decimal_separator != '.' && int_part.contains('.') {
+            return None;
+        }
+        match frac_part {
+            Some(frac) if self.grouping_separator.is_some_and(|g| frac.contains(g)) => None,
+            Some(frac) if self.decimal_separator != '.' && frac.contains('.') => None,
+            Some(frac) => Some(format!("{int_part}.{frac}")),
+            None => Some(int_part),
+        }
+    }
+}
+
+fn split_sign(s: &str) -> (&'static str, &str) {
+    if let Some(rest) = s.strip_prefix('-') {
+        ("-", rest)
+    } else if let Some(rest) = s.strip_prefix('+') {
+        ("", rest)
+    } else {
+        ("", s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn normalize_finance_values() {
+        let us = NumberFormat {
+            grouping_separator: Some(','),
+            percent: true,
+            currency_symbols: vec!["$".to_string()],
+            ..Default::default()
+        };
+        let eu = NumberFormat {
+            decimal_separator: ',',
+            grouping_separator: Some('.'),
+            ..Default::default()
+        };
+
+        let normalized = |format: &NumberFormat, raw| format.normalize(raw).map(|(s, m)| (s.into_owned(), m));
+
+        assert_eq!(normalized(&us, "1,234.56"), Some(("1234.56".into(), None)));
+        assert_eq!(normalized(&us, "12%"), Some(("12".into(), Some(NumericMarker::Percent))));
+        assert_eq!(
+            normalized(&us, "-$3.50"),
+            Some(("-3.50".into(), Some(NumericMarker::Currency("$".into()))))
+        );
+        assert_eq!(normalized(&us, "12,34"), None, "groups are made of 3 digits");
+        assert_eq!(normalized(&us, "$"), None);
+        assert_eq!(normalized(&eu, "1.234,56"), Some(("1234.56".into(), None)));
+        assert_eq!(normalized(&eu, "1.5"), None);
+    }
+}
@@ -0,0 +1,162 @@
+use std::{collections::BTreeMap, error::Error, fmt::Display, fs, path::Path, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// The type forced on a column, it replaces the inferred one
+/// both when parsing a `CsvDataset` and when generating the enums
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnType {
+    /// An enum with a variant for each unique value, also when values look numeric
+    Categorical,
+    /// Free text, the generated enum holds a `String`
+    Text,
+    Float,
+    /// An integer of the given rust type (`u8`, `i32`, ..)
+    Int(&'static str),
+}
+
+const INT_TYPES: [&str; 9] = ["u8", "u16", "u32", "u64", "i8", "i16", "i32", "i64", "i128"];
+
+impl ColumnType {
+    /// Check if an integer fits in the rust type of `Int`
+    pub fn int_fits(int_type: &str, int: i128) -> bool {
+        let (min, max) = match int_type {
+            "u8" => (0, u8::MAX as i128),
+            "u16" => (0, u16::MAX as i128),
+            "u32" => (0, u32::MAX as i128),
+            "u64" => (0, u64::MAX as i128),
+            "i8" => (i8::MIN as i128, i8::MAX as i128),
+            "i16" => (i16::MIN as i128, i16::MAX as i128),
+            "i32" => (i32::MIN as i128, i32::MAX as i128),
+            "i64" => (i64::MIN as i128, i64::MAX as i128),
+            _ => (i128::MIN, i128::MAX),
+        };
+        (min..=max).contains(&int)
+    }
+}
+
+impl FromStr for ColumnType {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "categorical" | "category" => Ok(ColumnType::Categorical),
+            "text" | "string" | "str" => Ok(ColumnType::Text),
+            "float" | "f64" => Ok(ColumnType::Float),
+            "int" => Ok(ColumnType::Int("i64")),
+            other => INT_TYPES
+                .into_iter()
+                .find(|ty| *ty == other)
+                .map(ColumnType::Int)
+                .ok_or_else(|| format!("Unknown column type: '{other}'")),
+        }
+    }
+}
+
+/// Serialized as its name, e.g. `"u8"`
+impl Serialize for ColumnType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ColumnType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+impl Display for ColumnType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ColumnType::Categorical => write!(f, "categorical"),
+            ColumnType::Text => write!(f, "text"),
+            ColumnType::Float => write!(f, "float"),
+            ColumnType::Int(int_type) => write!(f, "{int_type}"),
+        }
+    }
+}
+
+/// The column types forced by the user, keyed by raw column name.
+///
+/// It can be loaded from a toml file:
+/// ```toml
+/// [columns]
+/// zip = "string"
+/// age = "u8"
+/// status = "categorical"
+/// notes = "text"
+/// ```
+/// or the equivalent json `{"columns": {"zip": "string", ..}}`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TypeOverrides {
+    pub columns: BTreeMap<String, ColumnType>,
+}
+
+impl TypeOverrides {
+    pub fn get(&self, raw_column_name: &str) -> Option<ColumnType> {
+        self.columns.get(raw_column_name).copied()
+    }
+
+    pub fn insert(&mut self, raw_column_name: &str, column_type: ColumnType) {
+        self.columns.insert(raw_column_name.to_string(), column_type);
+    }
+
+    /// Load the overrides from a `.toml` or `.json` file
+    pub fn from_file(path: &Path) -> Result<Self, OverrideFileError> {
+        let content = fs::read_to_string(path).map_err(OverrideFileError::Io)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| OverrideFileError::Parse(e.to_string())),
+            Some("json") => {
+                serde_json::from_str(&content).map_err(|e| OverrideFileError::Parse(e.to_string()))
+            }
+            _ => Err(OverrideFileError::UnknownFormat),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum OverrideFileError {
+    Io(std::io::Error),
+    Parse(String),
+    UnknownFormat,
+}
+impl Error for OverrideFileError {}
+
+impl Display for OverrideFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Cannot read the overrides file: {e}"),
+            Self::Parse(e) => write!(f, "Invalid overrides file: {e}"),
+            Self::UnknownFormat => write!(f, "The overrides file should be a .toml or .json file"),
+        }
+    }
+}
+
+/// A cell which cannot be converted to the type forced on its column,
+/// it is replaced by `CsvAny::Null` in the dataset.
+/// A type forced on a column which is not in the csv is a violation too
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OverrideViolation {
+    pub column: String,
+    /// Index of the record, the header is not counted.
+    /// `None` when the column is not in the csv
+    pub row: Option<usize>,
+    pub raw: String,
+    pub expected: ColumnType,
+}
+
+impl Display for OverrideViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.row {
+            Some(row) => write!(
+                f,
+                "column `{}`, row {row}: `{}` is not a valid {}",
+                self.column, self.raw, self.expected
+            ),
+            None => write!(f, "column `{}` is forced to {} but it is not in the csv", self.column, self.expected),
+        }
+    }
+}
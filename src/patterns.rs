@@ -0,0 +1,364 @@
+use std::{fmt::Display, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+/// A well known shape of the strings of a column, see `Pattern::detect`.
+/// With `ParseOptions::semantic_types` a column where every string matches
+/// becomes `ColumnKind::Semantic` and holds the validated type of the pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Pattern {
+    Uuid,
+    HexHash,
+    Ipv4,
+    Ipv6,
+    Email,
+    Url,
+    Json,
+    Phone,
+}
+
+impl Pattern {
+    /// In the order they are tried by `detect`
+    pub const ALL: [Pattern; 8] = [
+        Pattern::Uuid,
+        Pattern::HexHash,
+        Pattern::Ipv4,
+        Pattern::Ipv6,
+        Pattern::Email,
+        Pattern::Url,
+        Pattern::Json,
+        Pattern::Phone,
+    ];
+
+    /// The first pattern matched by `s`, if any
+    pub fn detect(s: &str) -> Option<Pattern> {
+        Self::ALL.into_iter().find(|pattern| pattern.matches(s))
+    }
+
+    /// Whether `s` is accepted by the `FromStr` of the pattern type
+    pub fn matches(&self, s: &str) -> bool {
+        match self {
+            Pattern::Uuid => s.parse::<Uuid>().is_ok(),
+            Pattern::HexHash => s.parse::<HexHash>().is_ok(),
+            Pattern::Ipv4 => s.parse::<std::net::Ipv4Addr>().is_ok(),
+            Pattern::Ipv6 => s.parse::<std::net::Ipv6Addr>().is_ok(),
+            Pattern::Email => s.parse::<Email>().is_ok(),
+            Pattern::Url => s.parse::<Url>().is_ok(),
+            Pattern::Json => s.parse::<JsonObject>().is_ok(),
+            Pattern::Phone => s.parse::<Phone>().is_ok(),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pattern::Uuid => "uuid",
+            Pattern::HexHash => "hex_hash",
+            Pattern::Ipv4 => "ipv4",
+            Pattern::Ipv6 => "ipv6",
+            Pattern::Email => "email",
+            Pattern::Url => "url",
+            Pattern::Json => "json",
+            Pattern::Phone => "phone",
+        }
+    }
+
+    /// The path of the type used in the generated code
+    pub fn rust_type(&self) -> &'static str {
+        match self {
+            Pattern::Uuid => "csv_deserializer::patterns::Uuid",
+            Pattern::HexHash => "csv_deserializer::patterns::HexHash",
+            Pattern::Ipv4 => "std::net::Ipv4Addr",
+            Pattern::Ipv6 => "std::net::Ipv6Addr",
+            Pattern::Email => "csv_deserializer::patterns::Email",
+            Pattern::Url => "csv_deserializer::patterns::Url",
+            Pattern::Json => "csv_deserializer::patterns::JsonObject",
+            Pattern::Phone => "csv_deserializer::patterns::Phone",
+        }
+    }
+
+    /// The variant holding the value in the generated enum
+    pub fn variant(&self) -> &'static str {
+        match self {
+            Pattern::Uuid => "Uuid",
+            Pattern::HexHash => "HexHash",
+            Pattern::Ipv4 => "Ipv4",
+            Pattern::Ipv6 => "Ipv6",
+            Pattern::Email => "Email",
+            Pattern::Url => "Url",
+            Pattern::Json => "Json",
+            Pattern::Phone => "Phone",
+        }
+    }
+
+    /// Whether `rust_type` is `Copy`
+    pub fn is_copy(&self) -> bool {
+        matches!(self, Pattern::Uuid | Pattern::Ipv4 | Pattern::Ipv6)
+    }
+}
+
+impl Display for Pattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|pattern| pattern.name() == s.trim())
+            .ok_or_else(|| format!("Unknown pattern: '{s}'"))
+    }
+}
+
+/// How many strings of a column matched a pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PatternShare {
+    pub pattern: Pattern,
+    pub count: u32,
+    /// `count` over the strings of the column, between 0 and 1
+    pub share: f64,
+}
+
+impl Display for PatternShare {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:.1}%", self.pattern, self.share * 100.0)
+    }
+}
+
+/// A `String` newtype built only by a `FromStr` checking `$is_valid`.
+/// The types of the patterns are used by the generated code, so they are always serializable
+macro_rules! validated_string {
+    ($(#[$meta:meta])* $name:ident, $what:literal, $is_valid:expr) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        #[serde(try_from = "String", into = "String")]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = String;
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                let is_valid: fn(&str) -> bool = $is_valid;
+                match is_valid(s) {
+                    true => Ok(Self(s.to_string())),
+                    false => Err(format!("Invalid {}: '{s}'", $what)),
+                }
+            }
+        }
+
+        impl Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        impl TryFrom<String> for $name {
+            type Error = String;
+            fn try_from(s: String) -> Result<Self, Self::Error> {
+                s.parse()
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+    };
+}
+
+validated_string!(
+    /// `local@domain.tld`, without spaces
+    Email,
+    "email",
+    |s| {
+        let Some((local, domain)) = s.split_once('@') else {
+            return false;
+        };
+        let tld = domain.rsplit('.').next().unwrap_or_default();
+        !local.is_empty()
+            && !s.chars().any(char::is_whitespace)
+            && !domain.contains('@')
+            && domain.contains('.')
+            && domain.split('.').all(|label| !label.is_empty())
+            && tld.len() >= 2
+            && tld.chars().all(|c| c.is_ascii_alphabetic())
+    }
+);
+
+validated_string!(
+    /// An `http`, `https` or `ftp` url with a host
+    Url,
+    "url",
+    |s| {
+        let Some((scheme, rest)) = s.split_once("://") else {
+            return false;
+        };
+        let host = rest.split(['/', '?', '#']).next().unwrap_or_default();
+        matches!(scheme.to_ascii_lowercase().as_str(), "http" | "https" | "ftp")
+            && !host.is_empty()
+            && !s.chars().any(char::is_whitespace)
+    }
+);
+
+validated_string!(
+    /// 7 to 15 digits, with an optional `+` and ` -.()` separators.
+    /// Dates like `2024-01-15` are not phone numbers
+    Phone,
+    "phone number",
+    |s| {
+        let body = s.strip_prefix('+').unwrap_or(s);
+        let digits = body.chars().filter(char::is_ascii_digit).count();
+        let groups = body
+            .split(['-', '/', '.'])
+            .map(str::len)
+            .collect::<Vec<_>>();
+        body.chars().all(|c| c.is_ascii_digit() || " -.()".contains(c))
+            && (7..=15).contains(&digits)
+            && !matches!(groups.as_slice(), [4, 2, 2] | [2, 2, 4])
+    }
+);
+
+validated_string!(
+    /// A json object, `{ .. }`
+    JsonObject,
+    "json object",
+    |s| s.trim_start().starts_with('{')
+        && serde_json::from_str::<serde_json::Value>(s).is_ok_and(|value| value.is_object())
+);
+
+/// A hyphenated uuid, `67e55044-10b1-426f-9247-bb680e5fe0c8`, stored in 16 bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Uuid([u8; 16]);
+
+impl Uuid {
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hyphens_in_place = s.len() == 36
+            && s.char_indices()
+                .all(|(i, c)| matches!(i, 8 | 13 | 18 | 23) == (c == '-'));
+        let bytes = match hyphens_in_place {
+            true => hex_bytes(&s.replace('-', "")),
+            false => None,
+        };
+        bytes
+            .and_then(|bytes| bytes.try_into().ok())
+            .map(Uuid)
+            .ok_or_else(|| format!("Invalid uuid: '{s}'"))
+    }
+}
+
+impl Display for Uuid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                f.write_str("-")?;
+            }
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A md5, sha1, sha256 or sha512 digest in hex, stored as bytes
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct HexHash(Vec<u8>);
+
+impl HexHash {
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl FromStr for HexHash {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.len() {
+            32 | 40 | 64 | 128 => hex_bytes(s).map(HexHash),
+            _ => None,
+        }
+        .ok_or_else(|| format!("Invalid hex hash: '{s}'"))
+    }
+}
+
+impl Display for HexHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.iter().try_for_each(|byte| write!(f, "{byte:02x}"))
+    }
+}
+
+/// Serde goes through the string, like the csv cell
+macro_rules! string_conversions {
+    ($($name:ident),*) => {$(
+        impl TryFrom<String> for $name {
+            type Error = String;
+            fn try_from(s: String) -> Result<Self, Self::Error> {
+                s.parse()
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.to_string()
+            }
+        }
+    )*};
+}
+string_conversions!(Uuid, HexHash);
+
+/// `None` if `s` has an odd length or a non hex char
+fn hex_bytes(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) || !s.is_ascii() {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::Pattern;
+
+    #[test]
+    fn detect_the_shape_of_strings() {
+        let detected = [
+            "67E55044-10b1-426f-9247-bb680e5fe0c8",
+            "d41d8cd98f00b204e9800998ecf8427e",
+            "192.168.0.1",
+            "fe80::1",
+            "ada@example.org",
+            "https://example.org/a?b=c",
+            r#"{"a": [1, 2]}"#,
+            "+39 02 1234 5678",
+            "2024-01-15",
+            "setosa",
+        ]
+        .map(Pattern::detect);
+        use Pattern::*;
+        assert_eq!(
+            detected,
+            [Some(Uuid), Some(HexHash), Some(Ipv4), Some(Ipv6), Some(Email), Some(Url), Some(Json), Some(Phone), None, None]
+        );
+        let uuid = "67E55044-10b1-426f-9247-bb680e5fe0c8".parse::<super::Uuid>().unwrap();
+        assert_eq!(uuid.to_string(), "67e55044-10b1-426f-9247-bb680e5fe0c8");
+        assert!("67e55044-10b1-426f-9247-bb680e5fe0c".parse::<super::Uuid>().is_err());
+    }
+}
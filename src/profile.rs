@@ -0,0 +1,121 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::{
+    csv_dataset::CsvDataset,
+    dataset_info::{ColumnKind, render_type_counts},
+    patterns::PatternShare,
+    sketch::{Cardinality, ValueCount},
+};
+
+/// A summary of a column, to look at the data before generating the code
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ColumnProfile {
+    pub column: String,
+    pub kind: ColumnKind,
+    pub ints: u32,
+    pub floats: u32,
+    pub strings: u32,
+    pub nulls: u32,
+    pub empties: u32,
+    /// Unique values, `Null` and `Empty` are not counted
+    pub distinct: Cardinality,
+    /// The most frequent values, the most frequent first
+    pub top_values: Vec<ValueCount>,
+    /// The share of the strings matching each `Pattern`
+    pub patterns: Vec<PatternShare>,
+}
+
+/// The profile of every column, with at most `top` values each.
+/// The top values come from `ColumnInfo::top_values`, so `top` cannot exceed `HEAVY_HITTERS_CAPACITY`
+pub fn profile_dataset(dataset: &CsvDataset, top: usize) -> Vec<ColumnProfile> {
+    (0..dataset.names.len())
+        .map(|column_index| {
+            let col_info = dataset.column_info(column_index);
+            let mut top_values = col_info.top_values.clone();
+            top_values.truncate(top);
+            ColumnProfile {
+                column: col_info.column_name.raw.clone(),
+                kind: col_info.kind(),
+                ints: col_info.number_of_ints,
+                floats: col_info.number_of_floats,
+                strings: col_info.number_of_strings,
+                nulls: col_info.number_of_nulls,
+                empties: col_info.number_of_empties,
+                distinct: col_info.distinct,
+                top_values,
+                patterns: col_info.patterns,
+            }
+        })
+        .collect()
+}
+
+impl Display for ColumnProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let render = render_type_counts(self.empties, self.nulls, self.strings, self.floats, self.ints);
+        let top_values = self.top_values.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ");
+        let patterns = match self.patterns.as_slice() {
+            [] => String::new(),
+            patterns => format!(
+                "\n\tPatterns: {}",
+                patterns.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+            ),
+        };
+        write!(
+            f,
+            "{} ({}){render}\n\tDistinct: {}\n\tTop values: {top_values}{patterns}",
+            self.column, self.kind, self.distinct
+        )
+    }
+}
+
+/// The profiles as a markdown table, a row for each column
+pub fn markdown_table(profiles: &[ColumnProfile]) -> String {
+    let rows = profiles
+        .iter()
+        .map(|profile| {
+            let top_values = profile
+                .top_values
+                .iter()
+                .map(|value_count| format!("`{}` ({})", value_count.value.replace('|', "\\|"), value_count.count))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "| {} | {} | {} | {} | {} | {} | {} | {} | {top_values} |\n",
+                profile.column.replace('|', "\\|"),
+                profile.kind,
+                profile.ints,
+                profile.floats,
+                profile.strings,
+                profile.nulls,
+                profile.empties,
+                profile.distinct,
+            )
+        })
+        .collect::<String>();
+    format!(
+        "| Column | Type | Ints | Floats | Strings | Nulls | Empties | Distinct | Top values |\n\
+         |---|---|---|---|---|---|---|---|---|\n{rows}"
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{NullValues, csv_dataset::CsvDataset, sketch::Cardinality};
+
+    use super::{markdown_table, profile_dataset};
+
+    #[test]
+    fn most_frequent_values_first() {
+        let data = "species,petals\nsetosa,3\nvirginica,NA\nsetosa,\nsetosa,4\nversicolor,3\n";
+        let rdr = csv::ReaderBuilder::new().from_reader(data.as_bytes());
+        let df = CsvDataset::new(rdr, NullValues(vec!["NA"]));
+        let profiles = profile_dataset(&df, 2);
+
+        assert_eq!(profiles[0].distinct, Cardinality::Exact(3));
+        assert_eq!(profiles[0].to_string(), "species (categorical)\n\tStrings: 5\n\tDistinct: 3\n\tTop values: setosa (3), versicolor (1)");
+        assert_eq!((profiles[1].nulls, profiles[1].empties, profiles[1].distinct.count()), (1, 1, 2));
+        assert!(markdown_table(&profiles).contains("| petals | u8 | 3 | 0 | 0 | 1 | 1 | 2 | `3` (2), `4` (1) |"));
+    }
+}
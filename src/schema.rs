@@ -0,0 +1,221 @@
+use std::{error::Error, fmt::Display, fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    csv_dataset::CsvDataset,
+    csv_types::CsvAny,
+    dataset_info::{ColumnInfo, ColumnKind, composite_keys},
+    number_format::{NumberFormat, NumericMarker},
+    stats::NumericStats,
+};
+
+/// Everything the generators need to know about a csv, inferred from a `CsvDataset`.
+///
+/// It can be saved, edited by hand and loaded back, e.g. in toml:
+/// ```toml
+/// null_values = ["NA"]
+///
+/// [number_format]
+/// decimal_separator = "."
+/// percent = false
+/// currency_symbols = []
+///
+/// [[columns]]
+/// name = "species"
+/// ident = "species"
+/// type = "categorical"
+/// nullable = false
+/// categories = [{ raw = "Iris-setosa", ident = "Iris_setosa" }]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Schema {
+    /// Cells read as `Null`, e.g. `NA`
+    #[serde(default)]
+    pub null_values: Vec<String>,
+    #[serde(default)]
+    pub number_format: NumberFormat,
+    pub columns: Vec<SchemaColumn>,
+    /// Pairs of column names unique together, see `dataset_info::composite_keys`.
+    /// Only looked for with `ParseOptions::composite_keys`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub composite_keys: Vec<[String; 2]>,
+}
+
+/// A column of the `Schema`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchemaColumn {
+    /// The csv header
+    pub name: String,
+    /// The rust identifier of the column enum, the field is its lowercase
+    pub ident: String,
+    #[serde(rename = "type")]
+    pub kind: ColumnKind,
+    /// Whether the column contains `Null` or `Empty` values
+    pub nullable: bool,
+    /// Whether every value is unique, the generated frame can be indexed by it
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub key: bool,
+    /// The strings of a categorical or mixed column, each one gets a variant
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<Category>,
+    /// The rust type of the integers of a mixed column, see `ColumnInfo::int_type`.
+    /// `i64` when missing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub int_type: Option<String>,
+    /// Units stripped from the numeric values, only used in the docs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub markers: Vec<NumericMarker>,
+    /// Statistics of the numbers, only used in the docs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats: Option<NumericStats>,
+}
+
+/// A category and the name of its variant
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Category {
+    pub raw: String,
+    pub ident: String,
+}
+
+impl Schema {
+    pub fn from_dataset(dataset: &CsvDataset) -> Self {
+        let infos = (0..dataset.names.len())
+            .map(|column_index| dataset.column_info(column_index))
+            .collect::<Vec<_>>();
+        let composite_keys = match dataset.options.composite_keys {
+            true => composite_keys(&dataset.values, &infos)
+                .into_iter()
+                .map(|(a, b)| [dataset.names[a].raw.clone(), dataset.names[b].raw.clone()])
+                .collect(),
+            false => Vec::new(),
+        };
+        Self {
+            null_values: dataset.null_values.0.iter().map(|null| null.to_string()).collect(),
+            number_format: dataset.options.number_format.clone(),
+            columns: infos.iter().map(SchemaColumn::from).collect(),
+            composite_keys,
+        }
+    }
+
+    /// Load the schema from a `.toml` or `.json` file
+    pub fn from_file(path: &Path) -> Result<Self, SchemaFileError> {
+        let content = fs::read_to_string(path).map_err(SchemaFileError::Io)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::from_str(&content).map_err(|e| SchemaFileError::Parse(e.to_string())),
+            Some("json") => serde_json::from_str(&content).map_err(|e| SchemaFileError::Parse(e.to_string())),
+            _ => Err(SchemaFileError::UnknownFormat),
+        }
+    }
+
+    /// Save the schema as `.toml` or `.json`, depending on the extension of `path`
+    pub fn save(&self, path: &Path) -> Result<(), SchemaFileError> {
+        let content = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => toml::to_string(self).map_err(|e| SchemaFileError::Serialize(e.to_string()))?,
+            Some("json") => {
+                serde_json::to_string_pretty(self).map_err(|e| SchemaFileError::Serialize(e.to_string()))?
+            }
+            _ => return Err(SchemaFileError::UnknownFormat),
+        };
+        fs::write(path, content).map_err(SchemaFileError::Io)
+    }
+
+    /// The columns of `composite_keys`, the pairs naming a missing column are skipped
+    pub fn composite_key_columns(&self) -> Vec<(&SchemaColumn, &SchemaColumn)> {
+        let column = |name: &String| self.columns.iter().find(|column| &column.name == name);
+        self.composite_keys
+            .iter()
+            .filter_map(|[a, b]| Some((column(a)?, column(b)?)))
+            .collect()
+    }
+}
+
+impl From<&CsvDataset<'_>> for Schema {
+    fn from(dataset: &CsvDataset<'_>) -> Self {
+        Self::from_dataset(dataset)
+    }
+}
+
+impl From<&ColumnInfo> for SchemaColumn {
+    fn from(col_info: &ColumnInfo) -> Self {
+        let kind = col_info.kind();
+        let categories = match kind {
+            ColumnKind::Categorical | ColumnKind::Mixed => col_info
+                .unique_values
+                .iter()
+                .filter(|var| matches!(var.csvany, CsvAny::Str(_)))
+                .map(|var| Category {
+                    raw: var.raw.clone(),
+                    ident: var.sanitized.clone(),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        let categories = avoid_fixed_variants(categories, kind);
+        Self {
+            name: col_info.column_name.raw.clone(),
+            ident: col_info.column_name.sanitized.0.clone(),
+            kind,
+            nullable: col_info.is_nullable(),
+            key: col_info.is_candidate_key(),
+            categories,
+            int_type: match kind {
+                ColumnKind::Mixed => Some(col_info.int_type().to_string()),
+                _ => None,
+            },
+            markers: col_info.markers.clone(),
+            stats: col_info.stats.clone(),
+        }
+    }
+}
+
+impl SchemaColumn {
+    /// The name of the frame and row field holding the column
+    pub fn field_name(&self) -> String {
+        self.ident.to_lowercase()
+    }
+
+    /// The type of the `Int` variant of a mixed column
+    pub fn mixed_int_type(&self) -> &str {
+        self.int_type.as_deref().unwrap_or("i64")
+    }
+}
+
+/// Rename the categories named like the variants every generated enum can have,
+/// `Null` becomes `Null_`
+fn avoid_fixed_variants(mut categories: Vec<Category>, kind: ColumnKind) -> Vec<Category> {
+    let fixed: &[&str] = match kind {
+        ColumnKind::Mixed => &["Null", "Empty", "Other", "Int", "Float"],
+        _ => &["Null", "Empty", "Other"],
+    };
+    for index in 0..categories.len() {
+        while fixed.contains(&categories[index].ident.as_str())
+            || categories.iter().enumerate().any(|(other, category)| {
+                other != index && category.ident == categories[index].ident
+            })
+        {
+            categories[index].ident.push('_');
+        }
+    }
+    categories
+}
+
+#[derive(Debug)]
+pub enum SchemaFileError {
+    Io(std::io::Error),
+    Parse(String),
+    Serialize(String),
+    UnknownFormat,
+}
+impl Error for SchemaFileError {}
+
+impl Display for SchemaFileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "Cannot access the schema file: {e}"),
+            Self::Parse(e) => write!(f, "Invalid schema file: {e}"),
+            Self::Serialize(e) => write!(f, "Cannot write the schema: {e}"),
+            Self::UnknownFormat => write!(f, "The schema file should be a .toml or .json file"),
+        }
+    }
+}
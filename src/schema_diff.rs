@@ -0,0 +1,206 @@
+use std::fmt::Display;
+
+use serde::Serialize;
+
+use crate::{
+    dataset_info::ColumnKind,
+    gen_options::GenOptions,
+    schema::{Schema, SchemaColumn},
+};
+
+/// A difference between two `Schema`s, columns are matched by their csv header
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "snake_case")]
+pub enum SchemaChange {
+    ColumnAdded { column: String, kind: ColumnKind },
+    ColumnRemoved { column: String },
+    TypeChanged { column: String, from: ColumnKind, to: ColumnKind },
+    CategoryAdded { column: String, category: String },
+    CategoryRemoved { column: String, category: String },
+    /// The same category gets another variant name, e.g. after editing the schema
+    VariantRenamed { column: String, category: String, from: String, to: String },
+    /// The enum and the field of the column get another name
+    ColumnRenamed { column: String, from: String, to: String },
+    NullabilityChanged { column: String, nullable: bool },
+}
+
+impl SchemaChange {
+    /// Whether the code generated from the new schema breaks the code using the old one.
+    /// A new column adds a variant to the column enum, a field to the frame and the row
+    /// and changes the length of `get_columns`. A new category is rejected by the
+    /// conversion of the old code, unless the enum has an `Other` variant,
+    /// `#[non_exhaustive]` alone only keeps the `match`es compiling.
+    /// The nullability only changes the types in option mode
+    pub fn is_breaking(&self, options: &GenOptions) -> bool {
+        match self {
+            SchemaChange::CategoryAdded { .. } => !options.other_variant,
+            SchemaChange::ColumnAdded { .. }
+            | SchemaChange::ColumnRemoved { .. }
+            | SchemaChange::TypeChanged { .. }
+            | SchemaChange::CategoryRemoved { .. }
+            | SchemaChange::VariantRenamed { .. }
+            | SchemaChange::ColumnRenamed { .. } => true,
+            SchemaChange::NullabilityChanged { .. } => options.option_columns,
+        }
+    }
+}
+
+impl Display for SchemaChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ColumnAdded { column, kind } => write!(f, "column `{column}` added ({kind})"),
+            Self::ColumnRemoved { column } => write!(f, "column `{column}` removed"),
+            Self::TypeChanged { column, from, to } => {
+                write!(f, "column `{column}`: type changed from {from} to {to}")
+            }
+            Self::CategoryAdded { column, category } => {
+                write!(f, "column `{column}`: category `{category}` added")
+            }
+            Self::CategoryRemoved { column, category } => {
+                write!(f, "column `{column}`: category `{category}` removed")
+            }
+            Self::VariantRenamed { column, category, from, to } => {
+                write!(f, "column `{column}`: variant of `{category}` renamed from `{from}` to `{to}`")
+            }
+            Self::ColumnRenamed { column, from, to } => {
+                write!(f, "column `{column}`: enum and field renamed from `{from}` to `{to}`")
+            }
+            Self::NullabilityChanged { column, nullable: true } => write!(f, "column `{column}`: now nullable"),
+            Self::NullabilityChanged { column, nullable: false } => {
+                write!(f, "column `{column}`: no longer nullable")
+            }
+        }
+    }
+}
+
+/// The changes going from `old` to `new`, in the order of the columns
+pub fn diff_schemas(old: &Schema, new: &Schema) -> Vec<SchemaChange> {
+    let find = |schema: &Schema, name: &str| schema.columns.iter().find(|column| column.name == name).cloned();
+    let mut changes = Vec::new();
+    for old_column in &old.columns {
+        match find(new, &old_column.name) {
+            Some(new_column) => changes.extend(diff_columns(old_column, &new_column)),
+            None => changes.push(SchemaChange::ColumnRemoved {
+                column: old_column.name.clone(),
+            }),
+        }
+    }
+    changes.extend(
+        new.columns
+            .iter()
+            .filter(|column| find(old, &column.name).is_none())
+            .map(|column| SchemaChange::ColumnAdded {
+                column: column.name.clone(),
+                kind: column.kind,
+            }),
+    );
+    changes
+}
+
+fn diff_columns(old: &SchemaColumn, new: &SchemaColumn) -> Vec<SchemaChange> {
+    let column = &old.name;
+    let mut changes = Vec::new();
+    if old.kind != new.kind {
+        changes.push(SchemaChange::TypeChanged {
+            column: column.clone(),
+            from: old.kind,
+            to: new.kind,
+        });
+    }
+    if old.ident != new.ident {
+        changes.push(SchemaChange::ColumnRenamed {
+            column: column.clone(),
+            from: old.ident.clone(),
+            to: new.ident.clone(),
+        });
+    }
+    let has_category = |col: &SchemaColumn, raw: &str| col.categories.iter().any(|category| category.raw == raw);
+    changes.extend(
+        new.categories
+            .iter()
+            .filter(|category| !has_category(old, &category.raw))
+            .map(|category| SchemaChange::CategoryAdded {
+                column: column.clone(),
+                category: category.raw.clone(),
+            }),
+    );
+    changes.extend(
+        old.categories
+            .iter()
+            .filter(|category| !has_category(new, &category.raw))
+            .map(|category| SchemaChange::CategoryRemoved {
+                column: column.clone(),
+                category: category.raw.clone(),
+            }),
+    );
+    changes.extend(old.categories.iter().filter_map(|old_category| {
+        let new_category = new.categories.iter().find(|category| category.raw == old_category.raw)?;
+        (new_category.ident != old_category.ident).then(|| SchemaChange::VariantRenamed {
+            column: column.clone(),
+            category: old_category.raw.clone(),
+            from: old_category.ident.clone(),
+            to: new_category.ident.clone(),
+        })
+    }));
+    if old.nullable != new.nullable {
+        changes.push(SchemaChange::NullabilityChanged {
+            column: column.clone(),
+            nullable: new.nullable,
+        });
+    }
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{NullValues, csv_dataset::CsvDataset, gen_options::GenOptions, schema::Schema};
+
+    use super::{SchemaChange, diff_schemas};
+
+    #[test]
+    fn vendor_export_changes() {
+        let schema = |data: &'static str| {
+            let rdr = csv::ReaderBuilder::new().from_reader(data.as_bytes());
+            Schema::from_dataset(&CsvDataset::new(rdr, NullValues(vec!["NA"])))
+        };
+        let old = schema("id,status,note\n1,paid,a\n2,lost,b\n");
+        let new = schema("id,status,country\n1,paid,IT\n300,refunded,NA\n");
+        let changes = diff_schemas(&old, &new);
+        let shown = changes.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            shown,
+            [
+                "column `id`: type changed from u8 to u16",
+                "column `status`: category `refunded` added",
+                "column `status`: category `lost` removed",
+                "column `note` removed",
+                "column `country` added (categorical)",
+            ]
+        );
+        let breaking = |options: &GenOptions| changes.iter().filter(|change| change.is_breaking(options)).count();
+        assert_eq!(breaking(&GenOptions::default()), 5);
+        assert_eq!(breaking(&GenOptions { non_exhaustive: true, ..Default::default() }), 5);
+        assert_eq!(breaking(&GenOptions { other_variant: true, ..Default::default() }), 4);
+        assert!(matches!(changes[1], SchemaChange::CategoryAdded { .. }));
+    }
+
+    #[test]
+    fn renamed_variants_are_breaking() {
+        let rdr = csv::ReaderBuilder::new().from_reader("status\npaid\nlost\n".as_bytes());
+        let old = Schema::from_dataset(&CsvDataset::new(rdr, NullValues(vec!["NA"])));
+        let mut new = old.clone();
+        new.columns[0].ident = "Status".to_string();
+        new.columns[0].categories[0].ident = "Lost".to_string();
+
+        let changes = diff_schemas(&old, &new);
+        let shown = changes.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            shown,
+            [
+                "column `status`: enum and field renamed from `status` to `Status`",
+                "column `status`: variant of `lost` renamed from `lost` to `Lost`",
+            ]
+        );
+        assert!(changes.iter().all(|change| change.is_breaking(&GenOptions::default())));
+    }
+}
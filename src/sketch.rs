@@ -0,0 +1,174 @@
+use std::{
+    collections::{HashMap, hash_map::DefaultHasher},
+    hash::{Hash, Hasher},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// How many values `HeavyHitters` tracks, the counts are exact
+/// while the column has at most this many distinct values
+pub const HEAVY_HITTERS_CAPACITY: usize = 100;
+
+/// `2^HLL_PRECISION` registers, the standard error is about `1.04 / sqrt(registers)`, 1.6%
+const HLL_PRECISION: u32 = 12;
+
+/// A value and how many times it was seen.
+/// Like the other summaries of a column it is always serializable, `profile` prints it as json
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueCount {
+    pub value: String,
+    pub count: usize,
+}
+
+impl std::fmt::Display for ValueCount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} ({})", self.value, self.count)
+    }
+}
+
+/// The number of distinct values of a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "count", rename_all = "snake_case")]
+pub enum Cardinality {
+    Exact(usize),
+    /// Estimated with `HyperLogLog` once the unique values passed `ParseOptions::distinct_limit`
+    Approximate(usize),
+}
+
+impl Cardinality {
+    pub fn count(&self) -> usize {
+        match self {
+            Cardinality::Exact(count) | Cardinality::Approximate(count) => *count,
+        }
+    }
+}
+
+impl std::fmt::Display for Cardinality {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Cardinality::Exact(count) => write!(f, "{count}"),
+            Cardinality::Approximate(count) => write!(f, "~{count}"),
+        }
+    }
+}
+
+/// Estimates how many distinct values were pushed with a fixed amount of memory
+#[derive(Debug, Clone)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl Default for HyperLogLog {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; 1 << HLL_PRECISION],
+        }
+    }
+}
+
+impl HyperLogLog {
+    pub fn push<T: Hash + ?Sized>(&mut self, value: &T) {
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+        let index = (hash >> (64 - HLL_PRECISION)) as usize;
+        // position of the first 1 in the remaining bits
+        let rank = ((hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1))).leading_zeros() + 1;
+        self.registers[index] = self.registers[index].max(rank as u8);
+    }
+
+    pub fn estimate(&self) -> usize {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum = self.registers.iter().map(|&r| 2f64.powi(-i32::from(r))).sum::<f64>();
+        let raw = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|&&r| r == 0).count();
+        // linear counting is more precise for small cardinalities
+        let estimate = match raw <= 2.5 * m && zeros > 0 {
+            true => m * (m / zeros as f64).ln(),
+            false => raw,
+        };
+        estimate.round() as usize
+    }
+}
+
+/// The most frequent values in bounded memory (Space-Saving algorithm):
+/// when full, a new value replaces the least frequent one and inherits its count,
+/// so the counts of the values seen later are overestimated
+#[derive(Debug, Clone)]
+pub struct HeavyHitters {
+    capacity: usize,
+    counters: Vec<ValueCount>,
+    /// Position of each tracked value in `counters`
+    index: HashMap<String, usize>,
+}
+
+impl Default for HeavyHitters {
+    fn default() -> Self {
+        Self::with_capacity(HEAVY_HITTERS_CAPACITY)
+    }
+}
+
+impl HeavyHitters {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity,
+            counters: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn push(&mut self, value: &str) {
+        self.push_count(value, 1);
+    }
+
+    /// Like `push`, for a value seen `count` times
+    pub fn push_count(&mut self, value: &str, count: usize) {
+        if let Some(&position) = self.index.get(value) {
+            self.counters[position].count += count;
+        } else if self.counters.len() < self.capacity {
+            self.index.insert(value.to_string(), self.counters.len());
+            self.counters.push(ValueCount {
+                value: value.to_string(),
+                count,
+            });
+        } else if let Some(position) = (0..self.counters.len()).min_by_key(|&position| self.counters[position].count) {
+            let least = &mut self.counters[position];
+            self.index.remove(&least.value);
+            least.value = value.to_string();
+            least.count += count;
+            self.index.insert(least.value.clone(), position);
+        }
+    }
+
+    /// The tracked values, the most frequent first
+    pub fn top(&self) -> Vec<ValueCount> {
+        let mut top = self.counters.clone();
+        top.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+        top
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HeavyHitters, HyperLogLog};
+
+    #[test]
+    fn sketches_of_an_id_column() {
+        let mut hll = HyperLogLog::default();
+        (0..200_000).for_each(|id| hll.push(&id));
+        let estimate = hll.estimate() as f64;
+        assert!((estimate - 200_000.0).abs() / 200_000.0 < 0.05, "{estimate}");
+
+        let mut heavy_hitters = HeavyHitters::with_capacity(10);
+        (0..10_000).for_each(|id| {
+            heavy_hitters.push(&id.to_string());
+            if id % 2 == 0 {
+                heavy_hitters.push("N/A");
+            }
+        });
+        let top = heavy_hitters.top();
+        assert_eq!(top[0].value, "N/A");
+        assert!(top[0].count >= 5_000);
+    }
+}
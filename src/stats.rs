@@ -0,0 +1,209 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// Descriptive statistics of the numbers of a column.
+/// `median` and `p95` are exact up to `SKETCH_CAPACITY` values, approximated after.
+/// It is part of the `Schema`, so it is always serializable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NumericStats {
+    pub count: u64,
+    pub sum: f64,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Sample standard deviation, `0` with a single value
+    pub std_dev: f64,
+    pub median: f64,
+    pub p95: f64,
+    pub zeros: u64,
+    pub negatives: u64,
+    /// `inf` and `NaN` values, like a `1e400` cell, left out of the other statistics
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub non_finite: u64,
+}
+
+fn is_zero(x: &u64) -> bool {
+    *x == 0
+}
+
+/// How many values `QuantileSketch` keeps, the quantiles of bigger columns
+/// come from a uniform sample of this size
+pub const SKETCH_CAPACITY: usize = 10_000;
+
+/// Computes `NumericStats` in a single pass, see `push`
+#[derive(Debug, Clone, Default)]
+pub struct StreamingStats {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    mean: f64,
+    /// Sum of the squared distances from the mean (Welford)
+    m2: f64,
+    zeros: u64,
+    negatives: u64,
+    non_finite: u64,
+    sketch: QuantileSketch,
+}
+
+impl StreamingStats {
+    /// `inf` and `NaN` are only counted, they would poison the sum and the mean
+    pub fn push(&mut self, x: f64) {
+        if !x.is_finite() {
+            self.non_finite += 1;
+            return;
+        }
+        self.count += 1;
+        self.sum += x;
+        if self.count == 1 {
+            (self.min, self.max) = (x, x);
+        } else {
+            self.min = self.min.min(x);
+            self.max = self.max.max(x);
+        }
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+        self.zeros += u64::from(x == 0.0);
+        self.negatives += u64::from(x < 0.0);
+        self.sketch.push(x);
+    }
+
+    /// `None` if no number was pushed
+    pub fn finish(&self) -> Option<NumericStats> {
+        if self.count == 0 {
+            return None;
+        }
+        let std_dev = match self.count {
+            1 => 0.0,
+            n => (self.m2 / (n - 1) as f64).sqrt(),
+        };
+        Some(NumericStats {
+            count: self.count,
+            sum: self.sum,
+            min: self.min,
+            max: self.max,
+            mean: self.mean,
+            std_dev,
+            median: self.sketch.quantile(0.5),
+            p95: self.sketch.quantile(0.95),
+            zeros: self.zeros,
+            negatives: self.negatives,
+            non_finite: self.non_finite,
+        })
+    }
+}
+
+/// A reservoir sample of at most `SKETCH_CAPACITY` values,
+/// it holds every value until it is full
+#[derive(Debug, Clone, Default)]
+pub struct QuantileSketch {
+    sample: Vec<f64>,
+    seen: u64,
+    /// xorshift state, fixed so the same csv gives the same stats
+    rng: u64,
+}
+
+impl QuantileSketch {
+    pub fn push(&mut self, x: f64) {
+        self.seen += 1;
+        if self.sample.len() < SKETCH_CAPACITY {
+            self.sample.push(x);
+            return;
+        }
+        // Algorithm R: keep x with probability capacity / seen
+        let slot = self.next_random() % self.seen;
+        if let Some(kept) = self.sample.get_mut(slot as usize) {
+            *kept = x;
+        }
+    }
+
+    fn next_random(&mut self) -> u64 {
+        if self.rng == 0 {
+            self.rng = 0x9E37_79B9_7F4A_7C15;
+        }
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        self.rng
+    }
+
+    /// The `q` quantile (`0.5` is the median) interpolating between the closest values,
+    /// `NaN` if nothing was pushed
+    pub fn quantile(&self, q: f64) -> f64 {
+        let mut sorted = self.sample.clone();
+        sorted.sort_by(f64::total_cmp);
+        let Some(last) = sorted.len().checked_sub(1) else {
+            return f64::NAN;
+        };
+        let position = q.clamp(0.0, 1.0) * last as f64;
+        let (lo, hi) = (position.floor() as usize, position.ceil() as usize);
+        sorted[lo] + (sorted[hi] - sorted[lo]) * (position - lo as f64)
+    }
+}
+
+/// At most 4 decimals, without the trailing zeros
+fn short(x: f64) -> String {
+    let s = format!("{x:.4}");
+    match s.contains('.') {
+        true => s.trim_end_matches('0').trim_end_matches('.').to_string(),
+        false => s,
+    }
+}
+
+impl Display for NumericStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "min {}, max {}, mean {}, std dev {}, median {}, p95 {}, sum {}, zeros {}, negatives {}",
+            short(self.min),
+            short(self.max),
+            short(self.mean),
+            short(self.std_dev),
+            short(self.median),
+            short(self.p95),
+            short(self.sum),
+            self.zeros,
+            self.negatives
+        )?;
+        match self.non_finite {
+            0 => Ok(()),
+            n => write!(f, ", non finite {n}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SKETCH_CAPACITY, StreamingStats};
+
+    #[test]
+    fn exact_then_approximate_quantiles() {
+        let mut stats = StreamingStats::default();
+        [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0].into_iter().for_each(|x| stats.push(x));
+        let small = stats.finish().unwrap();
+        assert_eq!((small.min, small.max, small.mean, small.sum), (2.0, 9.0, 5.0, 40.0));
+        assert_eq!(small.median, 4.5);
+        assert!((small.std_dev - 2.138).abs() < 1e-3);
+
+        let mut stats = StreamingStats::default();
+        (0..SKETCH_CAPACITY * 10).for_each(|x| stats.push(x as f64 - 1000.0));
+        let big = stats.finish().unwrap();
+        let n = (SKETCH_CAPACITY * 10) as f64;
+        assert!((big.median - (n / 2.0 - 1000.0)).abs() < n * 0.02);
+        assert!((big.p95 - (n * 0.95 - 1000.0)).abs() < n * 0.02);
+        assert_eq!((big.zeros, big.negatives), (1, 1000));
+    }
+
+    #[test]
+    fn non_finite_values_are_only_counted() {
+        let mut stats = StreamingStats::default();
+        [1.0, f64::INFINITY, 3.0, f64::NAN].into_iter().for_each(|x| stats.push(x));
+        let stats = stats.finish().unwrap();
+        assert_eq!((stats.count, stats.mean, stats.max, stats.non_finite), (2, 2.0, 3.0, 2));
+
+        let json = serde_json::to_string(&stats).unwrap();
+        assert_eq!(serde_json::from_str::<super::NumericStats>(&json).unwrap(), stats);
+    }
+}
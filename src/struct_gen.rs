@@ -1,128 +1,634 @@
 #![allow(clippy::uninlined_format_args)]
 
 use crate::{
-    COLUMN_TYPE_ENUM_NAME, ColName, MAIN_STRUCT_NAME, SanitizedStr,
-    csv_dataset::CsvDataset,
-    csv_types::CsvAny,
-    dataset_info::{ColumnInfo, Variant},
+    dataset_info::ColumnKind,
+    enum_gen::gen_numeric_doc,
+    gen_options::GenOptions,
+    number_format::NumberFormat,
+    schema::{Schema, SchemaColumn},
 };
 
 /// It generates a struct named `CsvDataFrame` which
 /// contains all `Vec<T>` where T is the generated enums
 /// for each columns
-pub fn gen_struct(dataset: &CsvDataset) -> String {
-    let mut final_str = format!("pub struct {MAIN_STRUCT_NAME}{{\n");
+pub fn gen_struct(schema: &Schema, options: &GenOptions) -> String {
+    let frame_name = &options.names.frame;
+    let mut final_str = gen_error_enum(options);
+    final_str.push_str(options.serde_derive());
+    final_str.push_str(&format!("{} struct {frame_name}{{\n", options.visibility));
 
-    // final_str.push_str(&format!("\tpub columns: Vec<{COLUMN_TYPE_ENUM_NAME}>,\n"));
-    dataset.names.iter().for_each(|name| {
+    schema.columns.iter().for_each(|column| {
+        // numbers have no enum to document in option mode
+        let doc = match column.kind {
+            ColumnKind::Int(_) | ColumnKind::Float if options.option_columns => gen_numeric_doc(column, options) + "\n",
+            _ => String::new(),
+        };
         final_str.push_str(&format!(
-            "\tpub {}: {},\n",
-            name.sanitized.0.to_lowercase(),
-            COLUMN_TYPE_ENUM_NAME
+            "{doc}\tpub {}: Vec<{}>,\n",
+            column.field_name(),
+            options.element_type(column)
         ));
     });
     final_str.push('}');
 
     let impl_str_open = format!(
-        "impl {MAIN_STRUCT_NAME}{{
+        "impl {frame_name}{{
 "
     );
-    let new_method = gen_new_method(&dataset.names, &dataset.info);
-    let column_list_method = gen_column_list_method(&dataset.names);
+    let new_method = gen_new_method(&schema.columns, options);
+    let column_list_method = gen_column_list_method(&schema.columns, options);
     let impl_str_close = '}';
 
     final_str.push_str(&impl_str_open);
     final_str.push_str(&new_method);
     final_str.push_str(&column_list_method);
+    final_str.push_str(&gen_value_counts_methods(&schema.columns, options));
+    if options.other_variant {
+        final_str.push_str(&gen_other_counts_method(&schema.columns, options));
+    }
     final_str.push(impl_str_close);
+    final_str.push_str(&gen_try_from_impl(&schema.columns, options));
 
     final_str
 }
 
-fn gen_column_list_method(col_names: &[ColName]) -> String {
+/// `other_counts` returns, for each categorical column, how many values
+/// fell into the `Other(String)` variant
+fn gen_other_counts_method(columns: &[SchemaColumn], options: &GenOptions) -> String {
+    let counts = columns
+        .iter()
+        .filter(|column| options.has_other_variant(column))
+        .map(|column| {
+            let field = column.field_name();
+            let name = &column.ident;
+            let raw = &column.name;
+            // no closures, a binding could clash with a variant name
+            let flatten = match options.option_columns && column.nullable {
+                true => ".flatten()",
+                false => "",
+            };
+            format!("\t\t\t({raw:?}, self.{field}.iter(){flatten}.map({name}::is_other).map(usize::from).sum()),\n")
+        })
+        .collect::<String>();
+    format!(
+        "
+    /// How many values of each categorical column were unknown
+    /// when the code was generated
+    pub fn other_counts(&self) -> Vec<(&'static str, usize)> {{
+        vec![
+{counts}        ]
+    }}
+"
+    )
+}
+
+/// The error returned when a `CsvDataset` or a row doesn't match the generated types
+fn gen_error_enum(options: &GenOptions) -> String {
+    let error_name = &options.names.error;
+    let vis = options.visibility;
+    format!(
+        "
+#[derive(Debug, Clone, PartialEq)]
+{vis} enum {error_name} {{
+    MissingColumn {{ column: &'static str }},
+    InvalidValue {{
+        column: &'static str,
+        /// `None` when reading a single row with serde
+        row: Option<usize>,
+        raw: String,
+        expected: &'static str,
+    }},
+}}
+
+impl std::fmt::Display for {error_name} {{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {{
+        match self {{
+            Self::MissingColumn {{ column }} => write!(f, \"missing column `{{column}}`\"),
+            Self::InvalidValue {{ column, row: Some(row), raw, expected }} => {{
+                write!(f, \"column `{{column}}`, row {{row}}: `{{raw}}` is not {{expected}}\")
+            }}
+            Self::InvalidValue {{ column, row: None, raw, expected }} => {{
+                write!(f, \"column `{{column}}`: `{{raw}}` is not {{expected}}\")
+            }}
+        }}
+    }}
+}}
+
+impl std::error::Error for {error_name} {{}}
+
+"
+    )
+}
+
+/// A description of the values accepted by the column, used in the errors
+fn expected_description(column: &SchemaColumn) -> String {
+    let name = &column.ident;
+    match column.kind {
+        ColumnKind::Int(int_type) => format!("a valid {int_type}"),
+        ColumnKind::Float => "a valid f64".to_string(),
+        ColumnKind::Text => "a valid text".to_string(),
+        ColumnKind::Semantic(pattern) => format!("a valid {pattern}"),
+        ColumnKind::Categorical | ColumnKind::Mixed => format!("a `{name}` variant"),
+    }
+}
+
+/// `get_columns` returns every column wrapped in the `CsvColumn` view
+fn gen_column_list_method(columns: &[SchemaColumn], options: &GenOptions) -> String {
+    let column_enum = &options.names.column;
     let mut number_of_cols = 0;
-    let columns = col_names
+    let columns = columns
         .iter()
         .map(|x| {
             number_of_cols += 1;
-            format!("&self.{},", x.sanitized.0)
+            format!("{column_enum}::{}(&self.{}),", x.ident, x.field_name())
         })
         .collect::<String>();
     format!(
         "\
-    pub fn get_columns(&self)-> [&{COLUMN_TYPE_ENUM_NAME};{number_of_cols}] {{
+    pub fn get_columns(&self)-> [{column_enum}<'_>;{number_of_cols}] {{
         [{columns}]
     }}"
     )
 }
 
-fn gen_new_method(col_names: &[ColName], cols_info: &[ColumnInfo]) -> String {
-    let vecs_of_enums = cols_info
+/// `<column>_value_counts` for each categorical column,
+/// how many times each variant appears
+fn gen_value_counts_methods(columns: &[SchemaColumn], options: &GenOptions) -> String {
+    columns
         .iter()
-        .map(|col_info| gen_vec_of_enums(&col_info.column_name, &col_info.unique_values) + "\n\n")
-        .collect::<String>();
+        .filter(|column| column.kind == ColumnKind::Categorical)
+        .map(|column| {
+            let field = column.field_name();
+            let element_type = options.element_type(column);
+            let key = match options.is_copy(column) {
+                true => "*value",
+                false => "value.clone()",
+            };
+            format!(
+                "
+    /// How many times each value of `{}` appears
+    pub fn {field}_value_counts(&self) -> std::collections::HashMap<{element_type}, usize> {{
+        let mut counts = std::collections::HashMap::new();
+        for value in &self.{field} {{
+            *counts.entry({key}).or_default() += 1;
+        }}
+        counts
+    }}
+",
+                column.name
+            )
+        })
+        .collect()
+}
 
-    let fields_list = col_names
+/// `new` panics with the `TryFrom<&CsvDataset>` error
+fn gen_new_method(columns: &[SchemaColumn], options: &GenOptions) -> String {
+    let frame_name = &options.names.frame;
+    let converters = columns
         .iter()
-        .map(|colname| format!("{},\n\t\t\t", colname.sanitized.0.to_lowercase()))
+        .map(|column| gen_value_converter(column, options))
         .collect::<String>();
     format!(
         "\
+    /// Panics if `dataset` doesn't match the generated types,
+    /// use `{frame_name}::try_from` to handle the error
     pub fn new(dataset: &CsvDataset) -> Self{{
+        Self::try_from(dataset).unwrap_or_else(|e| panic!(\"{{e}}\"))
+    }}
+{converters}
+"
+    )
+}
+
+fn gen_try_from_impl(columns: &[SchemaColumn], options: &GenOptions) -> String {
+    let frame_name = &options.names.frame;
+    let error_name = &options.names.error;
+    let vecs_of_enums = columns
+        .iter()
+        .map(|column| gen_vec_of_enums(column, options) + "\n\n")
+        .collect::<String>();
+
+    let fields_list = columns
+        .iter()
+        .map(|column| format!("{},\n\t\t\t", column.field_name()))
+        .collect::<String>();
+    format!(
+        "
+impl TryFrom<&CsvDataset<'_>> for {frame_name}{{
+    type Error = {error_name};
+
+    fn try_from(dataset: &CsvDataset<'_>) -> Result<Self, Self::Error> {{
         {vecs_of_enums}
 
-        {MAIN_STRUCT_NAME}{{
+        Ok({frame_name}{{
             {fields_list}
-        }}
+        }})
     }}
+}}
 "
     )
 }
 
-fn gen_vec_of_enums(col_name: &ColName, unique_values: &[Variant]) -> String {
-    let ColName {
-        raw: _raw,
-        sanitized,
-    } = col_name;
-    let SanitizedStr(sanitized) = sanitized;
-    let sanitized_lower = sanitized.to_lowercase();
-    let mut float_case_already_written = false;
-    let mut int_case_already_written = false;
-    let mut str_case_already_written = false;
-    let match_arms = unique_values
-        .iter()
-        .filter_map(|v| match &v.csvany {
-            CsvAny::Str(_) if !str_case_already_written => {
-                str_case_already_written = true;
-                Some(format!(
-                    "CsvAny::Str(s) => {sanitized}::from_str(s).unwrap(),\n"
-                ))
-            }
-            CsvAny::Int(_) if !int_case_already_written => {
-                int_case_already_written = true;
-                Some(format!("CsvAny::Int(i) => {sanitized}::Int(*i),\n"))
-            }
-            CsvAny::Float(_) if !float_case_already_written => {
-                float_case_already_written = true;
-                Some(format!("CsvAny::Float(f) => {sanitized}::Float(*f),\n"))
-            }
-            CsvAny::Null => Some(format!("CsvAny::Null => {sanitized}::Null,\n")),
-            CsvAny::Empty => Some(format!("CsvAny::Empty => {sanitized}::Null,\n")),
-            _ => None,
-        })
-        .collect::<String>();
+fn gen_vec_of_enums(column: &SchemaColumn, options: &GenOptions) -> String {
+    let error_name = &options.names.error;
+    let SchemaColumn { name: raw, ident: sanitized, .. } = column;
+    let sanitized_lower = column.field_name();
+    let element_type = options.element_type(column);
+    let expected = expected_description(column);
     format!(
         "\
-let (index, _) = dataset
+let index = dataset
             .names
             .iter()
-            .enumerate()
-            .find(|(index, cl)| &cl.sanitized.0 == \"{sanitized}\")
-            .unwrap();
-let {sanitized_lower} = {COLUMN_TYPE_ENUM_NAME}::{sanitized}(dataset.values[index].iter().map(|val| match val{{
-    {match_arms}
-    _ => panic!(),
-}}).collect::<Vec<{sanitized}>>());
+            .position(|cl| cl.sanitized.0 == \"{sanitized}\")
+            .ok_or({error_name}::MissingColumn {{ column: {raw:?} }})?;
+let {sanitized_lower} = dataset.values[index].iter().enumerate().map(|(row, val)| {{
+    Self::{sanitized_lower}_from(val).ok_or_else(|| {error_name}::InvalidValue {{
+        column: {raw:?},
+        row: Some(row),
+        raw: val.to_string(),
+        expected: {expected:?},
+    }})
+}}).collect::<Result<Vec<{element_type}>, _>>()?;
     "
     )
 }
+
+/// A private associated function converting a `CsvAny` to a value of the column,
+/// `None` if it cannot be represented. Used both by `try_from` and by the row deserialization
+fn gen_value_converter(column: &SchemaColumn, options: &GenOptions) -> String {
+    let sanitized = &column.ident;
+    let sanitized_lower = column.field_name();
+    // in option mode values are `T` or `Some(T)`, otherwise the enum variant
+    let wrapper = |variant: &str| match (options.option_columns, column.nullable) {
+        (true, true) => "Some".to_string(),
+        (true, false) => String::new(),
+        (false, _) => format!("{sanitized}::{variant}"),
+    };
+    // `expr` is `T` or, when `fallible`, `Option<T>`
+    let convert = |variant: &str, expr: String, fallible: bool| {
+        let wrapper = wrapper(variant);
+        match (fallible, wrapper.is_empty()) {
+            (true, true) => expr,
+            (true, false) => format!("{expr}.map({wrapper})"),
+            (false, true) => format!("Some({expr})"),
+            (false, false) => format!("Some({wrapper}({expr}))"),
+        }
+    };
+    let int_expr = |int_type: &str, csvany: &str| match (int_type, csvany) {
+        ("i64", "Int") | ("i128", "BigInt") => convert("Int", "*i".to_string(), false),
+        ("i128", "Int") => convert("Int", "i128::from(*i)".to_string(), false),
+        _ => convert("Int", format!("{int_type}::try_from(*i).ok()"), true),
+    };
+    let mut match_arms = match column.kind {
+        ColumnKind::Mixed => return gen_mixed_converter(column, options),
+        ColumnKind::Int(int_type) => format!(
+            "CsvAny::Int(i) => {},\nCsvAny::BigInt(i) => {},\n",
+            int_expr(int_type, "Int"),
+            int_expr(int_type, "BigInt")
+        ),
+        ColumnKind::Float => format!(
+            "CsvAny::Float(f) => {},\nCsvAny::Int(i) => {},\nCsvAny::BigInt(i) => {},\n",
+            convert("Float", "*f".to_string(), false),
+            convert("Float", "*i as f64".to_string(), false),
+            convert("Float", "*i as f64".to_string(), false)
+        ),
+        ColumnKind::Text => format!(
+            "CsvAny::Str(s) => {},\nCsvAny::Int(_) | CsvAny::BigInt(_) | CsvAny::Float(_) => {},\n",
+            convert("Text", "s.clone()".to_string(), false),
+            convert("Text", "val.to_string()".to_string(), false)
+        ),
+        ColumnKind::Semantic(pattern) => {
+            let parse = |s: &str| format!("{s}.parse::<{}>().ok()", pattern.rust_type());
+            format!(
+                "CsvAny::Str(s) => {},\nCsvAny::Int(_) | CsvAny::BigInt(_) | CsvAny::Float(_) => {},\n",
+                convert(pattern.variant(), parse("s"), true),
+                convert(pattern.variant(), parse("val.to_string()"), true)
+            )
+        }
+        // the categorical enum is built by `from_str`, there is no variant to wrap it in
+        ColumnKind::Categorical if !options.option_columns => format!("CsvAny::Str(s) => {sanitized}::from_str(s).ok(),\n"),
+        ColumnKind::Categorical => format!("CsvAny::Str(s) => {},\n", convert("", format!("{sanitized}::from_str(s).ok()"), true)),
+    };
+    match_arms.push_str(&gen_missing_arms(column, options));
+    // unseen numbers in a categorical column are unknown categories too
+    let from_other = format!("{sanitized}::from_str(&other.to_string()).ok()");
+    let fallback_arm = match (options.has_other_variant(column), options.option_columns) {
+        (true, false) => format!("other => {from_other},"),
+        (true, true) => format!("other => {},", convert("", from_other, true)),
+        (false, _) => "_ => None,".to_string(),
+    };
+    let element_type = options.element_type(column);
+    format!(
+        "
+    fn {sanitized_lower}_from(val: &CsvAny) -> Option<{element_type}> {{
+        match val{{
+            {match_arms}
+            {fallback_arm}
+        }}
+    }}
+"
+    )
+}
+
+/// Missing values can show up in new data even if the column had none,
+/// but a non nullable `Vec<T>` has no way to store them
+fn gen_missing_arms(column: &SchemaColumn, options: &GenOptions) -> String {
+    let sanitized = &column.ident;
+    match (options.option_columns, column.nullable) {
+        (true, true) => "CsvAny::Null | CsvAny::Empty => Some(None),\n".to_string(),
+        (true, false) => String::new(),
+        (false, _) => format!(
+            "CsvAny::Null => Some({sanitized}::Null),\nCsvAny::Empty => Some({sanitized}::{}),\n",
+            options.empty_variant()
+        ),
+    }
+}
+
+/// Every `CsvAny` has its own variant in the mixed enum,
+/// strings go through `from_str` which knows the named variants
+fn gen_mixed_converter(column: &SchemaColumn, options: &GenOptions) -> String {
+    let name = &column.ident;
+    let field = column.field_name();
+    let element_type = options.element_type(column);
+    let option_nullable = options.option_columns && column.nullable;
+    // `expr` is the enum or, when `fallible`, `Option` of it
+    let wrap = |expr: String, fallible: bool| match (option_nullable, fallible) {
+        (true, true) => format!("{expr}.map(Some)"),
+        (true, false) => format!("Some(Some({expr}))"),
+        (false, true) => expr,
+        (false, false) => format!("Some({expr})"),
+    };
+    // like `int_expr` of `gen_value_converter`
+    let int_arm = |csvany: &str| match (column.mixed_int_type(), csvany) {
+        ("i64", "Int") | ("i128", "BigInt") => (format!("{name}::Int(*i)"), false),
+        ("i128", "Int") => (format!("{name}::Int(i128::from(*i))"), false),
+        (int_type, _) => (format!("{int_type}::try_from(*i).ok().map({name}::Int)"), true),
+    };
+    let (int, int_fallible) = int_arm("Int");
+    let (big_int, big_int_fallible) = int_arm("BigInt");
+    let arms = [
+        ("CsvAny::Str(s)", format!("{name}::from_str(s).ok()"), true),
+        ("CsvAny::Int(i)", int, int_fallible),
+        ("CsvAny::BigInt(i)", big_int, big_int_fallible),
+        ("CsvAny::Float(f)", format!("{name}::Float(*f)"), false),
+    ]
+    .into_iter()
+    .map(|(pattern, expr, fallible)| format!("{pattern} => {},\n", wrap(expr, fallible)))
+    .collect::<String>();
+    let missing_arms = gen_missing_arms(column, options);
+    // only a non nullable option column leaves `Null` and `Empty` unmatched
+    let fallback_arm = match options.option_columns && !column.nullable {
+        true => "_ => None,",
+        false => "",
+    };
+    format!(
+        "
+    fn {field}_from(val: &CsvAny) -> Option<{element_type}> {{
+        match val{{
+            {arms}{missing_arms}
+            {fallback_arm}
+        }}
+    }}
+"
+    )
+}
+
+/// It generates a struct named `CsvRow` with a field for each column,
+/// `CsvDataFrame::rows` to iterate over them and `FromIterator<CsvRow>`
+/// to collect them back in a `CsvDataFrame`.
+///
+/// `CsvRow` also implements `Deserialize`, so it can be read
+/// straight from a `csv::Reader` with `reader.deserialize()`,
+/// cells are converted with the null values and number format of `schema`
+pub fn gen_row_struct(schema: &Schema, options: &GenOptions) -> String {
+    let frame_name = &options.names.frame;
+    let row_name = &options.names.row;
+    let error_name = &options.names.error;
+    let vis = options.visibility;
+    let fields = schema.columns.iter()
+        .map(|column| {
+            // `Serialize` writes back the csv headers, `Deserialize` goes through the raw row
+            let rename = match options.serde {
+                true => format!("\t#[serde(rename = {:?})]\n", column.name),
+                false => String::new(),
+            };
+            format!(
+                "{rename}\tpub {}: {},\n",
+                column.field_name(),
+                options.element_type(column)
+            )
+        })
+        .collect::<String>();
+    let raw_fields = schema.columns.iter()
+        .map(|column| {
+            format!(
+                "\t#[serde(rename = {:?})]\n\t{}: String,\n",
+                column.name,
+                column.field_name()
+            )
+        })
+        .collect::<String>();
+    let from_raw_fields = schema.columns.iter()
+        .map(|column| {
+            let field = column.field_name();
+            // read like the dataset column, whose type can be forced by an override
+            let csvany = format!(
+                "CsvAny::parse_as(&raw.{field}, {}, &null_values, &number_format)",
+                gen_column_kind(column.kind)
+            );
+            let raw = &column.name;
+            let expected = expected_description(column);
+            format!(
+                "\t\t\t{field}: {frame_name}::{field}_from(&{csvany}).ok_or_else(|| {error_name}::InvalidValue {{
+                column: {raw:?},
+                row: None,
+                raw: raw.{field}.clone(),
+                expected: {expected:?},
+            }})?,\n"
+            )
+        })
+        .collect::<String>();
+    let rows_fields = schema.columns.iter()
+        .map(|column| {
+            let field = column.field_name();
+            match options.is_copy(column) {
+                true => format!("{field}: self.{field}[index], "),
+                false => format!("{field}: self.{field}[index].clone(), "),
+            }
+        })
+        .collect::<String>();
+    let index_methods = gen_index_methods(schema, options, &rows_fields);
+    let push_fields = schema.columns.iter()
+        .map(|column| {
+            let field = column.field_name();
+            format!("\t\t\tdf.{field}.push(row.{field});\n")
+        })
+        .collect::<String>();
+    let empty_fields = schema.columns.iter()
+        .map(|column| format!("{}: Vec::new(), ", column.field_name()))
+        .collect::<String>();
+    let len = match schema.columns.first() {
+        Some(column) => format!("self.{}.len()", column.field_name()),
+        None => "0".to_string(),
+    };
+    let null_values = schema
+        .null_values
+        .iter()
+        .map(|null| format!("{null:?}, "))
+        .collect::<String>();
+    let number_format = gen_number_format(&schema.number_format);
+    let serialize = match options.serde {
+        true => "csv_deserializer::serde::Serialize, ",
+        false => "",
+    };
+
+    format!(
+        "
+#[derive(Debug, Clone, PartialEq, {serialize}csv_deserializer::serde::Deserialize)]
+#[serde(crate = \"csv_deserializer::serde\", try_from = \"Raw{row_name}\")]
+{vis} struct {row_name}{{
+{fields}}}
+
+#[derive(csv_deserializer::serde::Deserialize)]
+#[serde(crate = \"csv_deserializer::serde\")]
+struct Raw{row_name}{{
+{raw_fields}}}
+
+impl TryFrom<Raw{row_name}> for {row_name}{{
+    type Error = {error_name};
+
+    fn try_from(raw: Raw{row_name}) -> Result<Self, Self::Error> {{
+        let null_values = csv_deserializer::NullValues(vec![{null_values}]);
+        let number_format = {number_format};
+        Ok({row_name}{{
+{from_raw_fields}        }})
+    }}
+}}
+
+impl {frame_name}{{
+    pub fn len(&self) -> usize {{
+        {len}
+    }}
+
+    pub fn is_empty(&self) -> bool {{
+        self.len() == 0
+    }}
+
+    pub fn rows(&self) -> impl Iterator<Item = {row_name}> + '_ {{
+        (0..self.len()).map(|index| {row_name}{{ {rows_fields}}})
+    }}
+{index_methods}}}
+
+impl FromIterator<{row_name}> for {frame_name}{{
+    fn from_iter<I: IntoIterator<Item = {row_name}>>(iter: I) -> Self {{
+        let mut df = {frame_name}{{ {empty_fields}}};
+        for row in iter {{
+{push_fields}        }}
+        df
+    }}
+}}
+"
+    )
+}
+
+/// `index_by_<col>` and `get_by_<col>` for the key columns of the schema, and
+/// `index_by_<a>_and_<b>` and `get_by_<a>_and_<b>` for its composite keys.
+/// `rows_fields` builds the row at `index`, like in `rows`
+fn gen_index_methods(schema: &Schema, options: &GenOptions, rows_fields: &str) -> String {
+    let row_name = &options.names.row;
+    // the value of `column` at `row`
+    let key = |column: &SchemaColumn| match options.is_copy(column) {
+        true => format!("self.{}[row]", column.field_name()),
+        false => format!("self.{}[row].clone()", column.field_name()),
+    };
+    let single = schema.columns.iter().filter(|column| column.key).map(|column| {
+        let field = column.field_name();
+        let element_type = options.element_type(column);
+        let raw = &column.name;
+        let key = key(column);
+        format!(
+            "
+    /// The row index of each `{raw}`, every value was unique in the csv used for the generation.
+    /// A repeated value keeps its first row, like `get_by_{field}`
+    pub fn index_by_{field}(&self) -> std::collections::HashMap<{element_type}, usize> {{
+        let mut rows = std::collections::HashMap::with_capacity(self.len());
+        for row in 0..self.len() {{
+            rows.entry({key}).or_insert(row);
+        }}
+        rows
+    }}
+
+    /// The first row whose `{raw}` is `key`, `index_by_{field}` is faster for many lookups
+    pub fn get_by_{field}(&self, key: &{element_type}) -> Option<{row_name}> {{
+        let index = self.{field}.iter().position(|value| value == key)?;
+        Some({row_name}{{ {rows_fields}}})
+    }}
+"
+        )
+    });
+    let composite = schema.composite_key_columns().into_iter().map(|(a, b)| {
+        let (field_a, field_b) = (a.field_name(), b.field_name());
+        let (type_a, type_b) = (options.element_type(a), options.element_type(b));
+        let (raw_a, raw_b) = (&a.name, &b.name);
+        let (key_a, key_b) = (key(a), key(b));
+        format!(
+            "
+    /// The row index of each `({raw_a}, {raw_b})`, every pair was unique in the csv used for the generation.
+    /// A repeated pair keeps its first row
+    pub fn index_by_{field_a}_and_{field_b}(&self) -> std::collections::HashMap<({type_a}, {type_b}), usize> {{
+        let mut rows = std::collections::HashMap::with_capacity(self.len());
+        for row in 0..self.len() {{
+            rows.entry(({key_a}, {key_b})).or_insert(row);
+        }}
+        rows
+    }}
+
+    /// The first row whose `{raw_a}` is `first` and `{raw_b}` is `second`
+    pub fn get_by_{field_a}_and_{field_b}(&self, first: &{type_a}, second: &{type_b}) -> Option<{row_name}> {{
+        let index = (0..self.len()).find(|&row| &self.{field_a}[row] == first && &self.{field_b}[row] == second)?;
+        Some({row_name}{{ {rows_fields}}})
+    }}
+"
+        )
+    });
+    single.chain(composite).collect()
+}
+
+/// The rust expression of `kind`
+fn gen_column_kind(kind: ColumnKind) -> String {
+    let path = "csv_deserializer::dataset_info::ColumnKind";
+    match kind {
+        ColumnKind::Int(int_type) => format!("{path}::Int({int_type:?})"),
+        ColumnKind::Float => format!("{path}::Float"),
+        ColumnKind::Text => format!("{path}::Text"),
+        ColumnKind::Categorical => format!("{path}::Categorical"),
+        ColumnKind::Mixed => format!("{path}::Mixed"),
+        ColumnKind::Semantic(pattern) => format!(
+            "{path}::Semantic(csv_deserializer::patterns::Pattern::{})",
+            pattern.variant()
+        ),
+    }
+}
+
+/// The rust expression building `number_format`
+fn gen_number_format(number_format: &NumberFormat) -> String {
+    let NumberFormat {
+        decimal_separator,
+        grouping_separator,
+        percent,
+        currency_symbols,
+    } = number_format;
+    let currency_symbols = currency_symbols
+        .iter()
+        .map(|symbol| format!("{symbol:?}.to_string(), "))
+        .collect::<String>();
+    format!(
+        "csv_deserializer::number_format::NumberFormat {{
+            decimal_separator: {decimal_separator:?},
+            grouping_separator: {grouping_separator:?},
+            percent: {percent},
+            currency_symbols: vec![{currency_symbols}],
+        }}"
+    )
+}
@@ -0,0 +1,205 @@
+use std::{collections::BTreeMap, fmt::Display, io};
+
+use csv::Reader;
+use serde::Serialize;
+
+use crate::{
+    NullValues,
+    csv_types::CsvAny,
+    dataset_info::ColumnKind,
+    gen_options::GenOptions,
+    overrides::ColumnType,
+    schema::{Schema, SchemaColumn},
+};
+
+/// Why a cell wouldn't convert to the generated types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CellProblem {
+    /// A string which is not one of the categories, without `GenOptions::other_variant`
+    UnknownCategory,
+    /// A value which cannot be represented by the type of the column
+    WrongType,
+    /// A missing value in a column which had none, with `GenOptions::option_columns`
+    UnexpectedNull,
+}
+
+impl Display for CellProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownCategory => write!(f, "unknown category"),
+            Self::WrongType => write!(f, "wrong type"),
+            Self::UnexpectedNull => write!(f, "unexpected null"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct InvalidCell {
+    pub column: String,
+    /// Index of the record, the header is not counted
+    pub row: usize,
+    pub raw: String,
+    pub expected: ColumnKind,
+    pub problem: CellProblem,
+}
+
+impl Display for InvalidCell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Self { column, row, raw, expected, problem } = self;
+        match problem {
+            CellProblem::UnknownCategory => write!(f, "column `{column}`, row {row}: `{raw}` is an unknown category"),
+            CellProblem::WrongType => write!(f, "column `{column}`, row {row}: `{raw}` is not a valid {expected}"),
+            CellProblem::UnexpectedNull => write!(f, "column `{column}`, row {row}: unexpected missing value"),
+        }
+    }
+}
+
+/// Every problem found by `validate_csv`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    pub rows: usize,
+    /// Columns of the schema without a header in the csv
+    pub missing_columns: Vec<String>,
+    pub invalid_cells: Vec<InvalidCell>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.missing_columns.is_empty() && self.invalid_cells.is_empty()
+    }
+
+    /// How many invalid cells for each column and problem
+    pub fn counts(&self) -> BTreeMap<String, BTreeMap<CellProblem, usize>> {
+        let mut counts: BTreeMap<String, BTreeMap<CellProblem, usize>> = BTreeMap::new();
+        for cell in &self.invalid_cells {
+            *counts
+                .entry(cell.column.clone())
+                .or_default()
+                .entry(cell.problem)
+                .or_default() += 1;
+        }
+        counts
+    }
+}
+
+/// Check every cell of the csv against `schema`, without building the
+/// generated `CsvDataFrame` which would stop at the first error.
+/// The cells are read with the null values and number format of the schema,
+/// like the generated `CsvRow` does
+pub fn validate_csv<R: io::Read>(
+    mut reader: Reader<R>,
+    schema: &Schema,
+    options: &GenOptions,
+) -> Result<ValidationReport, csv::Error> {
+    let headers = reader.headers()?.clone();
+    let null_values = NullValues(schema.null_values.iter().map(String::as_str).collect());
+    let mut report = ValidationReport::default();
+    let columns = schema
+        .columns
+        .iter()
+        .filter_map(|column| match headers.iter().position(|header| header == column.name) {
+            Some(index) => Some((index, column)),
+            None => {
+                report.missing_columns.push(column.name.clone());
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    for (row, record) in reader.into_records().enumerate() {
+        let record = record?;
+        report.rows += 1;
+        for (index, column) in &columns {
+            let raw = record.get(*index).unwrap_or_default();
+            let value = CsvAny::parse_as(raw, column.kind, &null_values, &schema.number_format);
+            if let Some(problem) = check_value(&value, column, options) {
+                report.invalid_cells.push(InvalidCell {
+                    column: column.name.clone(),
+                    row,
+                    raw: raw.to_string(),
+                    expected: column.kind,
+                    problem,
+                });
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// The same checks done by the converters of the generated code
+fn check_value(value: &CsvAny, column: &SchemaColumn, options: &GenOptions) -> Option<CellProblem> {
+    let is_category = |s: &str| options.other_variant || column.categories.iter().any(|category| category.raw == s);
+    let valid = match (column.kind, value) {
+        // the enums always have a `Null` variant, only `Vec<T>` can't hold a missing value
+        (_, CsvAny::Null | CsvAny::Empty) if options.option_columns && !column.nullable => {
+            return Some(CellProblem::UnexpectedNull);
+        }
+        (_, CsvAny::Null | CsvAny::Empty) => true,
+        (ColumnKind::Categorical | ColumnKind::Mixed, CsvAny::Str(s)) if !is_category(s) => {
+            return Some(CellProblem::UnknownCategory);
+        }
+        (ColumnKind::Categorical | ColumnKind::Mixed | ColumnKind::Text, CsvAny::Str(_)) => true,
+        (ColumnKind::Semantic(pattern), CsvAny::Str(s)) => pattern.matches(s),
+        (ColumnKind::Int(int_type), CsvAny::Int(i)) => ColumnType::int_fits(int_type, i128::from(*i)),
+        (ColumnKind::Int(int_type), CsvAny::BigInt(i)) => ColumnType::int_fits(int_type, *i),
+        (ColumnKind::Float, CsvAny::Float(_) | CsvAny::Int(_) | CsvAny::BigInt(_)) => true,
+        (ColumnKind::Mixed, CsvAny::Int(i)) => ColumnType::int_fits(column.mixed_int_type(), i128::from(*i)),
+        (ColumnKind::Mixed, CsvAny::BigInt(i)) => ColumnType::int_fits(column.mixed_int_type(), *i),
+        (ColumnKind::Mixed, CsvAny::Float(_)) => true,
+        _ => false,
+    };
+    match valid {
+        true => None,
+        false => Some(CellProblem::WrongType),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{NullValues, csv_dataset::CsvDataset, gen_options::GenOptions, schema::Schema};
+
+    use super::{CellProblem, validate_csv};
+
+    #[test]
+    fn every_invalid_cell_is_reported() {
+        let rdr = csv::ReaderBuilder::new().from_reader("age,species\n3,setosa\n4,virginica\n".as_bytes());
+        let schema = Schema::from_dataset(&CsvDataset::new(rdr, NullValues(vec!["NA"])));
+
+        let new_data = "age,species\n300,setosa\nNA,versicolor\nfive,virginica\n";
+        let rdr = csv::ReaderBuilder::new().from_reader(new_data.as_bytes());
+        let options = GenOptions {
+            option_columns: true,
+            ..Default::default()
+        };
+        let report = validate_csv(rdr, &schema, &options).unwrap();
+        let shown = report.invalid_cells.iter().map(ToString::to_string).collect::<Vec<_>>();
+        assert_eq!(
+            shown,
+            [
+                "column `age`, row 0: `300` is not a valid u8",
+                "column `age`, row 1: unexpected missing value",
+                "column `species`, row 1: `versicolor` is an unknown category",
+                "column `age`, row 2: `five` is not a valid u8",
+            ]
+        );
+        assert_eq!(report.counts()["age"][&CellProblem::WrongType], 2);
+        assert!(!report.is_valid());
+
+        // `age::Null` holds the missing value
+        let rdr = csv::ReaderBuilder::new().from_reader(new_data.as_bytes());
+        let report = validate_csv(rdr, &schema, &GenOptions::default()).unwrap();
+        assert!(!report.counts()["age"].contains_key(&CellProblem::UnexpectedNull));
+    }
+
+    #[test]
+    fn whole_numbers_are_valid_floats() {
+        let rdr = csv::ReaderBuilder::new().from_reader("score\n2.5\n4.5\n".as_bytes());
+        let schema = Schema::from_dataset(&CsvDataset::new(rdr, NullValues(vec!["NA"])));
+
+        let new_data = "score\n3\n170141183460469231731687303715884105727\n";
+        let rdr = csv::ReaderBuilder::new().from_reader(new_data.as_bytes());
+        assert!(validate_csv(rdr, &schema, &GenOptions::default()).unwrap().is_valid());
+        let code = crate::code_gen::gen_rust_code(&schema, &GenOptions::default());
+        assert!(code.contains("CsvAny::Int(i) => Some(score::Float(*i as f64)),"));
+    }
+}